@@ -94,6 +94,10 @@ pub struct SourceDiagnostic {
     /// Additonal hints to the user, indicating how this problem could be avoided
     /// or worked around.
     pub hints: Vec<EcoString>,
+    /// Whether the error must not be caught by scripting, e.g. by
+    /// `{func.try-call}`. This is the case for stray control flow and for
+    /// exceeding the maximum call depth.
+    pub uncatchable: bool,
 }
 
 /// The severity of a [`SourceDiagnostic`].
@@ -114,6 +118,7 @@ impl SourceDiagnostic {
             trace: vec![],
             message: message.into(),
             hints: vec![],
+            uncatchable: false,
         }
     }
 
@@ -125,6 +130,7 @@ impl SourceDiagnostic {
             trace: vec![],
             message: message.into(),
             hints: vec![],
+            uncatchable: false,
         }
     }
 
@@ -144,6 +150,12 @@ impl SourceDiagnostic {
         self.hints.extend(hints);
         self
     }
+
+    /// Marks the diagnostic as [uncatchable](Self::uncatchable).
+    pub fn uncatchable(mut self) -> Self {
+        self.uncatchable = true;
+        self
+    }
 }
 
 impl From<SyntaxError> for SourceDiagnostic {
@@ -154,6 +166,7 @@ impl From<SyntaxError> for SourceDiagnostic {
             message: error.message,
             trace: vec![],
             hints: error.hints,
+            uncatchable: false,
         }
     }
 }
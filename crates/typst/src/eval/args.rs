@@ -76,6 +76,12 @@ impl Args {
         self.items.iter().filter(|slot| slot.name.is_none()).count()
     }
 
+    /// Returns the names of the remaining named arguments, in the order in
+    /// which they were passed.
+    pub fn remaining_names(&self) -> impl Iterator<Item = &Str> + '_ {
+        self.items.iter().filter_map(|slot| slot.name.as_ref())
+    }
+
     /// Push a positional argument.
     pub fn push(&mut self, span: Span, value: Value) {
         self.items.push(Arg {
@@ -272,3 +278,35 @@ impl Repr for Arg {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(args: &Args) -> Vec<&str> {
+        args.remaining_names().map(Str::as_str).collect()
+    }
+
+    #[test]
+    fn test_remaining_names() {
+        let span = Span::detached();
+        let named = |name: &str, value: i64| Arg {
+            span,
+            name: Some(name.into()),
+            value: Spanned::new(value.into_value(), span),
+        };
+
+        let mut args = Args::new(span, [1i64]);
+        args.items.push(named("b", 2));
+        args.push(span, 3i64.into_value());
+        args.items.push(named("a", 4));
+        args.items.push(named("c", 5));
+        assert_eq!(names(&args), ["b", "a", "c"]);
+
+        // Taken arguments are no longer reported, positional ones never are.
+        assert_eq!(args.eat::<i64>().unwrap(), Some(1));
+        assert_eq!(args.named::<i64>("a").unwrap(), Some(4));
+        assert_eq!(names(&args), ["b", "c"]);
+        assert_eq!(args.to_pos().len(), 1);
+    }
+}
@@ -1,20 +1,39 @@
 use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter};
-use std::num::NonZeroI64;
+use std::num::{NonZeroI64, NonZeroUsize};
 use std::ops::{Add, AddAssign};
 
 use ecow::{eco_format, EcoString, EcoVec};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    cast, func, ops, scope, ty, Args, Bytes, CastInfo, FromValue, Func, IntoValue,
-    Reflect, Repr, Value, Version, Vm,
+    cast, dict, func, ops, scope, ty, Args, Bytes, CastInfo, Datetime, Dict, FromValue,
+    Func, IntoValue, Reflect, Regex, Repr, Str, Value, Version, Vm,
 };
-use crate::diag::{At, SourceResult, StrResult};
+use crate::diag::{bail, At, SourceResult, StrResult};
 use crate::eval::ops::{add, mul};
+use crate::geom::{Length, Numeric};
+use crate::model::Content;
 use crate::syntax::Span;
 use crate::util::pretty_array_like;
 
+/// The maximum number of items an array may have when its length is derived
+/// from a user-provided count, e.g. when repeating or filling an array.
+///
+/// This is a fixed limit rather than a setting: the checks run in plain value
+/// operations like `*`, which have no access to the world, and their results
+/// are memoized, so they must not depend on state outside their inputs.
+const MAX_LEN: usize = 1 << 24;
+
+/// Ensure that an array with `len` items would not exceed [`MAX_LEN`].
+fn check_len(len: usize) -> StrResult<()> {
+    if len > MAX_LEN {
+        bail!("resulting array too large");
+    }
+    Ok(())
+}
+
 /// Create a new [`Array`] from values.
 #[macro_export]
 #[doc(hidden)]
@@ -70,7 +89,8 @@ pub use ecow::eco_vec;
 ///     .join(", ", last: " and "))
 /// ```
 #[ty(scope)]
-#[derive(Default, Clone, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Default, Clone, Hash, Serialize, Deserialize)]
+#[allow(clippy::derived_hash_with_manual_eq)]
 #[serde(transparent)]
 pub struct Array(EcoVec<Value>);
 
@@ -136,15 +156,152 @@ impl Array {
             .filter(|&v| v < self.0.len() + end_ok as usize)
     }
 
+    /// Zip the array with any number of other arrays, stopping at the end of
+    /// the shortest one. Each item of the result is an array with one item
+    /// from each input.
+    pub fn zip_all(&self, others: Vec<Array>) -> Array {
+        let mut out = Self::with_capacity(self.len());
+        let mut iterators = others.into_iter().map(|i| i.into_iter()).collect::<Vec<_>>();
+
+        for this in self.iter() {
+            let mut row = Self::with_capacity(1 + iterators.len());
+            row.push(this.clone());
+
+            for iterator in &mut iterators {
+                let Some(item) = iterator.next() else {
+                    return out;
+                };
+
+                row.push(item);
+            }
+
+            out.push(row.into_value());
+        }
+
+        out
+    }
+
+    /// Evaluate the predicate of [`retain`](Self::retain) or
+    /// [`extract`](Self::extract) for each item.
+    pub(super) fn test_mask(
+        &self,
+        vm: &mut Vm,
+        test: &Func,
+        method: &'static str,
+    ) -> SourceResult<Vec<bool>> {
+        self.iter()
+            .map(|item| {
+                let args = Args::new(test.span(), [item.clone()]);
+                test.call_in(vm, args, method)?.cast::<bool>().at(test.span())
+            })
+            .collect()
+    }
+
+    /// Keep only the items for which the mask is true and return how many
+    /// were removed.
+    pub(super) fn retain_by(&mut self, keep: Vec<bool>) -> i64 {
+        let len = self.len();
+        let mut keep = keep.into_iter();
+        self.0.retain(|_| keep.next().unwrap_or(true));
+        (len - self.len()) as i64
+    }
+
+    /// Remove the items for which the mask is true and return them.
+    pub(super) fn extract_by(&mut self, mask: Vec<bool>) -> Array {
+        let mut extracted = EcoVec::new();
+        let mut mask = mask.into_iter();
+        self.0.retain(|item| {
+            let hit = mask.next().unwrap_or(false);
+            if hit {
+                extracted.push(item.clone());
+            }
+            !hit
+        });
+        extracted.into()
+    }
+
+    /// Map all items with the function and combine the results with the
+    /// operation. Returns the default if the array is empty.
+    fn fold_by(
+        &self,
+        vm: &mut Vm,
+        span: Span,
+        mapper: &Func,
+        method: &'static str,
+        default: Option<Value>,
+        op: fn(Value, Value) -> StrResult<Value>,
+    ) -> SourceResult<Option<Value>> {
+        let mut acc = None;
+        for item in self.iter() {
+            let args = Args::new(mapper.span(), [item.clone()]);
+            let value = mapper.call_in(vm, args, method)?;
+            acc = Some(match acc {
+                Some(acc) => op(acc, value).at(span)?,
+                None => value,
+            });
+        }
+        Ok(acc.or(default))
+    }
+
+    /// Select the first `n` items in the given order without sorting the
+    /// whole array.
+    fn select(
+        &self,
+        n: usize,
+        cmp: impl Fn(&Value, &Value) -> StrResult<Ordering>,
+    ) -> StrResult<Array> {
+        let mut out: EcoVec<Value> = EcoVec::with_capacity(n.min(self.len()));
+        if n == 0 {
+            return Ok(out.into());
+        }
+
+        for item in self.iter() {
+            // Find the position after all selected items that are not
+            // greater, so that equal items keep their order.
+            let (mut lo, mut hi) = (0, out.len());
+            while lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                if cmp(item, &out[mid])? == Ordering::Less {
+                    hi = mid;
+                } else {
+                    lo = mid + 1;
+                }
+            }
+
+            if lo < n {
+                if out.len() == n {
+                    out.pop();
+                }
+                out.insert(lo, item.clone());
+            }
+        }
+
+        Ok(out.into())
+    }
+
     /// Repeat this array `n` times.
     pub fn repeat(&self, n: usize) -> StrResult<Self> {
         let count = self
             .len()
             .checked_mul(n)
             .ok_or_else(|| format!("cannot repeat this array {} times", n))?;
+        check_len(count)?;
 
         Ok(self.iter().cloned().cycle().take(count).collect())
     }
+
+    /// Cast all values in the array to `T`.
+    ///
+    /// The error message contains the index of the first value that could
+    /// not be cast.
+    pub fn cast_all<T: FromValue>(self) -> StrResult<Vec<T>> {
+        self.into_iter()
+            .enumerate()
+            .map(|(i, value)| {
+                value.cast().map_err(|err| eco_format!("{err} (at index {i})"))
+            })
+            .collect()
+    }
 }
 
 #[scope]
@@ -174,37 +331,99 @@ impl Array {
     }
 
     /// Returns the first item in the array. May be used on the left-hand side
-    /// of an assignment. Fails with an error if the array is empty.
+    /// of an assignment. Returns the default value if the array is empty or
+    /// fails with an error if no default value was specified.
     #[func]
-    pub fn first(&self) -> StrResult<Value> {
-        self.0.first().cloned().ok_or_else(array_is_empty)
+    pub fn first(
+        &self,
+        /// A default value to return if the array is empty.
+        #[named]
+        default: Option<Value>,
+    ) -> StrResult<Value> {
+        self.0.first().cloned().or(default).ok_or_else(array_is_empty)
     }
 
     /// Returns the last item in the array. May be used on the left-hand side of
-    /// an assignment. Fails with an error if the array is empty.
+    /// an assignment. Returns the default value if the array is empty or fails
+    /// with an error if no default value was specified.
+    #[func]
+    pub fn last(
+        &self,
+        /// A default value to return if the array is empty.
+        #[named]
+        default: Option<Value>,
+    ) -> StrResult<Value> {
+        self.0.last().cloned().or(default).ok_or_else(array_is_empty)
+    }
+
+    /// Splits off the first item of the array. Returns an array with the first
+    /// item and an array of the remaining ones. Fails with an error if the
+    /// array is empty.
+    ///
+    /// ```example
+    /// #let (head, tail) = (1, 2, 3).split-first()
+    /// #head \
+    /// #tail
+    /// ```
+    #[func]
+    pub fn split_first(&self) -> StrResult<Array> {
+        let (first, rest) = self.0.split_first().ok_or_else(array_is_empty)?;
+        Ok(array![first.clone(), Array::from(rest)])
+    }
+
+    /// Splits off the last item of the array. Returns an array with an array
+    /// of the preceding items and the last item. Fails with an error if the
+    /// array is empty.
+    ///
+    /// ```example
+    /// #let (init, last) = (1, 2, 3).split-last()
+    /// #init \
+    /// #last
+    /// ```
     #[func]
-    pub fn last(&self) -> StrResult<Value> {
-        self.0.last().cloned().ok_or_else(array_is_empty)
+    pub fn split_last(&self) -> StrResult<Array> {
+        let (last, rest) = self.0.split_last().ok_or_else(array_is_empty)?;
+        Ok(array![Array::from(rest), last.clone()])
     }
 
     /// Returns the item at the specified index in the array. May be used on the
     /// left-hand side of an assignment. Returns the default value if the index
     /// is out of bounds or fails with an error if no default value was
     /// specified.
+    ///
+    /// When given an array of indices, for instance a [`range`]($array.range),
+    /// returns an array with the items at each of them instead.
+    ///
+    /// ```example
+    /// #let values = ("a", "b", "c", "d")
+    /// #values.at(1) \
+    /// #values.at(range(1, 3))
+    /// ```
     #[func]
     pub fn at(
         &self,
         /// The index at which to retrieve the item. If negative, indexes from
         /// the back.
-        index: i64,
+        index: ArrayIndex,
         /// A default value to return if the index is out of bounds.
         #[named]
         default: Option<Value>,
     ) -> StrResult<Value> {
-        self.locate_opt(index, false)
-            .and_then(|i| self.0.get(i).cloned())
-            .or(default)
-            .ok_or_else(|| out_of_bounds_no_default(index, self.len()))
+        let get = |index: i64| {
+            self.locate_opt(index, false)
+                .and_then(|i| self.0.get(i).cloned())
+                .or_else(|| default.clone())
+                .ok_or_else(|| out_of_bounds_no_default(index, self.len()))
+        };
+
+        match index {
+            ArrayIndex::Single(index) => get(index),
+            ArrayIndex::Many(indices) => indices
+                .into_iter()
+                .map(get)
+                .collect::<StrResult<_>>()
+                .map(Value::Array),
+        }
     }
 
     /// Adds a value to the end of the array.
@@ -240,7 +459,39 @@ impl Array {
         Ok(())
     }
 
-    /// Removes the value at the specified index from the array and return it.
+    /// Inserts all items of another array at the specified index, preserving
+    /// their order. Fails with an error if the index is out of bounds.
+    ///
+    /// ```example
+    /// #let values = (1, 2, 5)
+    /// #values.insert-many(2, (3, 4))
+    /// #values
+    /// ```
+    #[func]
+    pub fn insert_many(
+        &mut self,
+        /// The index at which to insert the items. If negative, indexes from
+        /// the back.
+        index: i64,
+        /// The values to insert into the array.
+        values: Array,
+    ) -> StrResult<()> {
+        let i = self.locate(index, true)?;
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        let mut vec = EcoVec::with_capacity(self.len() + values.len());
+        vec.extend(self.0[..i].iter().cloned());
+        vec.extend(values);
+        vec.extend(self.0[i..].iter().cloned());
+        self.0 = vec;
+        Ok(())
+    }
+
+    /// Removes the value at the specified index from the array and returns it.
+    /// Returns the default value if the index is out of bounds or fails with an
+    /// error if no default value was specified.
     #[func]
     pub fn remove(
         &mut self,
@@ -257,8 +508,70 @@ impl Array {
             .ok_or_else(|| out_of_bounds_no_default(index, self.len()))
     }
 
+    /// Exchanges the values at the two specified indices. Fails with an error
+    /// if either index is out of bounds.
+    #[func]
+    pub fn swap(
+        &mut self,
+        /// The index of the first item. If negative, indexes from the back.
+        first: i64,
+        /// The index of the second item. If negative, indexes from the back.
+        second: i64,
+    ) -> StrResult<()> {
+        let i = self.locate(first, false)?;
+        let j = self.locate(second, false)?;
+        if i != j {
+            self.0.make_mut().swap(i, j);
+        }
+        Ok(())
+    }
+
+    /// Replaces the items in the specified range with the items of another
+    /// array and returns the removed items. Fails with an error if the start
+    /// or end index is out of bounds.
+    ///
+    /// ```example
+    /// #let values = (1, 2, 3, 4, 5)
+    /// #values.splice(1, 3, ("a", "b", "c")) \
+    /// #values.splice(4) \
+    /// #values
+    /// ```
+    #[func]
+    pub fn splice(
+        &mut self,
+        /// The start index (inclusive). If negative, indexes from the back.
+        start: i64,
+        /// The end index (exclusive). If omitted or `{none}`, everything from
+        /// the start onwards is replaced. If negative, indexes from the back.
+        #[default]
+        end: Option<i64>,
+        /// The items to insert in place of the removed ones. If omitted, the
+        /// items are just removed.
+        #[default]
+        replacement: Array,
+    ) -> StrResult<Array> {
+        let start = self.locate(start, true)?;
+        let end = self.locate(end.unwrap_or(self.len() as i64), true)?.max(start);
+        let removed = self.0[start..end].into();
+
+        let len = self.len() - (end - start) + replacement.len();
+        let mut vec = EcoVec::with_capacity(len);
+        vec.extend(self.0[..start].iter().cloned());
+        vec.extend(replacement);
+        vec.extend(self.0[end..].iter().cloned());
+        self.0 = vec;
+
+        Ok(removed)
+    }
+
     /// Extracts a subslice of the array. Fails with an error if the start or
     /// index is out of bounds.
+    ///
+    /// ```example
+    /// #range(10).slice(2, 8, step: 2) \
+    /// #range(10).slice(8, 2, step: -2) \
+    /// #range(5).slice(-1, step: -1)
+    /// ```
     #[func]
     pub fn slice(
         &self,
@@ -272,14 +585,36 @@ impl Array {
         /// `start + count` as the `end` position. Mutually exclusive with `end`.
         #[named]
         count: Option<i64>,
+        /// The distance between the extracted items. If negative, the slice
+        /// walks backwards from `start` towards `end`, yielding the items in
+        /// reverse order. In this case, omitting `end` extracts everything up
+        /// to and including the first item.
+        #[named]
+        #[default(NonZeroI64::new(1).unwrap())]
+        step: NonZeroI64,
     ) -> StrResult<Array> {
         let mut end = end;
         if end.is_none() {
             end = count.map(|c: i64| start + c);
         }
-        let start = self.locate(start, true)?;
-        let end = self.locate(end.unwrap_or(self.len() as i64), true)?.max(start);
-        Ok(self.0[start..end].into())
+
+        let step = step.get();
+        let skip = step.unsigned_abs() as usize;
+        if step > 0 {
+            let start = self.locate(start, true)?;
+            let end = self.locate(end.unwrap_or(self.len() as i64), true)?.max(start);
+            return Ok(self.0[start..end].iter().step_by(skip).cloned().collect());
+        }
+
+        // When walking backwards, the start is the upper (inclusive) bound and
+        // the end is the lower (exclusive) bound.
+        let upper = (self.locate(start, true)? + 1).min(self.len());
+        let lower = match end {
+            Some(end) => self.locate(end, true)? + 1,
+            None => 0,
+        };
+        let lower = lower.min(upper);
+        Ok(self.0[lower..upper].iter().rev().step_by(skip).cloned().collect())
     }
 
     /// Whether the array contains the specified value.
@@ -295,19 +630,84 @@ impl Array {
         self.0.contains(&value)
     }
 
+    /// Returns the index of the first occurrence of the specified value or
+    /// `{none}` if the array does not contain it. To search for the first
+    /// item matching a condition instead, use
+    /// [`position`]($array.position).
+    #[func]
+    pub fn index_of(
+        &self,
+        /// The value to search for.
+        value: Value,
+    ) -> Option<i64> {
+        self.iter().position(|item| *item == value).map(|i| i as i64)
+    }
+
+    /// Returns the index of the last occurrence of the specified value or
+    /// `{none}` if the array does not contain it.
+    #[func]
+    pub fn last_index_of(
+        &self,
+        /// The value to search for.
+        value: Value,
+    ) -> Option<i64> {
+        self.iter().rposition(|item| *item == value).map(|i| i as i64)
+    }
+
+    /// Searches a sorted array for the specified value.
+    ///
+    /// Returns a dictionary of the form `{(found: true, index: 2)}`. If the
+    /// value is contained in the array, `found` is `{true}` and `index` is the
+    /// index of a matching item. Otherwise, `found` is `{false}` and `index` is
+    /// the position at which the value could be inserted to keep the array
+    /// sorted. The array must be sorted in the order used by
+    /// [`sorted`]($array.sorted), otherwise the result is meaningless. Fails
+    /// with an error if the value cannot be compared with an item.
+    ///
+    /// ```example
+    /// #(1, 3, 5, 7).binary-search(5) \
+    /// #(1, 3, 5, 7).binary-search(4)
+    /// ```
+    #[func]
+    pub fn binary_search(
+        &self,
+        /// The value to search for.
+        value: Value,
+    ) -> StrResult<Dict> {
+        let (mut lo, mut hi) = (0, self.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match sort_cmp(&self.0[mid], &value)? {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => {
+                    return Ok(dict! { "found" => true, "index" => mid as i64 });
+                }
+            }
+        }
+        Ok(dict! { "found" => false, "index" => lo as i64 })
+    }
+
     /// Searches for an item for which the given function returns `{true}` and
     /// returns the first match or `{none}` if there is no match.
+    ///
+    /// Instead of a function, you can also pass a [regular expression]($regex)
+    /// to search for the first string that matches it.
+    ///
+    /// ```example
+    /// #("a", "b1", "c2").find(regex("\d"))
+    /// ```
     #[func]
     pub fn find(
         &self,
         /// The virtual machine.
         vm: &mut Vm,
-        /// The function to apply to each item. Must return a boolean.
-        searcher: Func,
+        /// The function to apply to each item, which must return a boolean,
+        /// or a regular expression that string items must match.
+        searcher: Searcher,
     ) -> SourceResult<Option<Value>> {
         for item in self.iter() {
-            let args = Args::new(searcher.span(), [item.clone()]);
-            if searcher.call_vm(vm, args)?.cast::<bool>().at(searcher.span())? {
+            if searcher.matches(vm, item, "find")? {
                 return Ok(Some(item.clone()));
             }
         }
@@ -316,17 +716,28 @@ impl Array {
 
     /// Searches for an item for which the given function returns `{true}` and
     /// returns the index of the first match or `{none}` if there is no match.
+    /// Like [`find`]($array.find), this also accepts a regular expression.
+    ///
+    /// This is the predicate-based counterpart to
+    /// [`index-of`]($array.index-of). Where `find` returns the matching item
+    /// itself, `position` returns its index, so `{array.at(array.position(f))}`
+    /// is the same as `{array.find(f)}` whenever there is a match.
+    ///
+    /// ```example
+    /// #(1, 4, 3, 8).position(calc.even) \
+    /// #("a", "b1", "c2").position(regex("\d"))
+    /// ```
     #[func]
     pub fn position(
         &self,
         /// The virtual machine.
         vm: &mut Vm,
-        /// The function to apply to each item. Must return a boolean.
-        searcher: Func,
+        /// The function to apply to each item, which must return a boolean,
+        /// or a regular expression that string items must match.
+        searcher: Searcher,
     ) -> SourceResult<Option<i64>> {
         for (i, item) in self.iter().enumerate() {
-            let args = Args::new(searcher.span(), [item.clone()]);
-            if searcher.call_vm(vm, args)?.cast::<bool>().at(searcher.span())? {
+            if searcher.matches(vm, item, "position")? {
                 return Ok(Some(i as i64));
             }
         }
@@ -334,6 +745,64 @@ impl Array {
         Ok(None)
     }
 
+    /// Searches for all items for which the given function returns `{true}`
+    /// and returns their indices. Like [`position`]($array.position), but
+    /// collects every match instead of stopping at the first one.
+    ///
+    /// ```example
+    /// #(1, 4, 3, 8, 6).find-all(calc.even)
+    /// ```
+    #[func]
+    pub fn find_all(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The function to apply to each item, which must return a boolean,
+        /// or a regular expression that string items must match.
+        searcher: Searcher,
+    ) -> SourceResult<Array> {
+        let mut indices = EcoVec::new();
+        for (i, item) in self.iter().enumerate() {
+            if searcher.matches(vm, item, "find-all")? {
+                indices.push(Value::Int(i as i64));
+            }
+        }
+        Ok(indices.into())
+    }
+
+    /// Searches for all windows of consecutive items for which the given
+    /// function returns `{true}` and returns the indices at which they start.
+    /// The function is called with each window as an array.
+    ///
+    /// ```example
+    /// #(1, 2, 3, 5, 6, 7).find-windows(
+    ///   3, w => w.at(0) + 2 == w.at(2),
+    /// )
+    /// ```
+    #[func]
+    pub fn find_windows(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The number of items in each window. Must be positive.
+        size: NonZeroUsize,
+        /// The function to apply to each window. Must return a boolean.
+        test: Func,
+    ) -> SourceResult<Array> {
+        let mut indices = EcoVec::new();
+        for (i, window) in self.0.windows(size.get()).enumerate() {
+            let args = Args::new(test.span(), [Value::Array(window.into())]);
+            if test
+                .call_in(vm, args, "find-windows")?
+                .cast::<bool>()
+                .at(test.span())?
+            {
+                indices.push(Value::Int(i as i64));
+            }
+        }
+        Ok(indices.into())
+    }
+
     /// Create an array consisting of a sequence of numbers.
     ///
     /// If you pass just one positional parameter, it is interpreted as the
@@ -388,6 +857,46 @@ impl Array {
         Ok(array)
     }
 
+    /// Create an array consisting of the same value repeated a number of
+    /// times.
+    ///
+    /// ```example
+    /// #array.fill(0, 4) \
+    /// #array.fill("ab", 2)
+    /// ```
+    #[func]
+    pub fn fill(
+        /// The value to fill the array with.
+        value: Value,
+        /// How many times the value should be repeated.
+        count: usize,
+    ) -> StrResult<Array> {
+        check_len(count)?;
+        Ok(std::iter::repeat(value).take(count).collect())
+    }
+
+    /// Concatenates any number of arrays into a single one.
+    ///
+    /// This produces the same result as adding the arrays with `+`, but only
+    /// allocates once.
+    ///
+    /// ```example
+    /// #array.concat((1, 2), (3,), (4, 5))
+    /// ```
+    #[func]
+    pub fn concat(
+        /// The arrays to concatenate.
+        #[variadic]
+        arrays: Vec<Array>,
+    ) -> Array {
+        let len = arrays.iter().map(Array::len).sum();
+        let mut out = EcoVec::with_capacity(len);
+        for array in arrays {
+            out.extend(array);
+        }
+        out.into()
+    }
+
     /// Produces a new array with only the items from the original one for which
     /// the given function returns true.
     #[func]
@@ -398,44 +907,162 @@ impl Array {
         /// The function to apply to each item. Must return a boolean.
         test: Func,
     ) -> SourceResult<Array> {
-        let mut kept = EcoVec::new();
+        let mut kept = EcoVec::with_capacity(self.len());
         for item in self.iter() {
             let args = Args::new(test.span(), [item.clone()]);
-            if test.call_vm(vm, args)?.cast::<bool>().at(test.span())? {
+            if test.call_in(vm, args, "filter")?.cast::<bool>().at(test.span())? {
                 kept.push(item.clone())
             }
         }
         Ok(kept.into())
     }
 
-    /// Produces a new array in which all items from the original one were
-    /// transformed with the given function.
+    /// Removes all items for which the given function returns `{false}` from
+    /// the array and returns how many were removed. This is the in-place
+    /// counterpart to [`filter`]($array.filter).
+    ///
+    /// ```example
+    /// #let values = (1, 2, 3, 4, 5)
+    /// #values.retain(calc.odd) \
+    /// #values
+    /// ```
     #[func]
-    pub fn map(
-        &self,
+    pub fn retain(
+        &mut self,
         /// The virtual machine.
         vm: &mut Vm,
-        /// The function to apply to each item.
-        mapper: Func,
-    ) -> SourceResult<Array> {
-        self.iter()
-            .map(|item| {
-                let args = Args::new(mapper.span(), [item.clone()]);
-                mapper.call_vm(vm, args)
-            })
-            .collect()
+        /// The function to apply to each item. Must return a boolean.
+        test: Func,
+    ) -> SourceResult<i64> {
+        let keep = self.test_mask(vm, &test, "retain")?;
+        Ok(self.retain_by(keep))
     }
 
-    /// Returns a new array with the values alongside their indices.
+    /// Removes all items for which the given function returns `{true}` from
+    /// the array and returns them. The remaining items keep their order.
     ///
-    /// The returned array consists of `(index, value)` pairs in the form of
-    /// length-2 arrays. These can be [destructured]($scripting/#bindings) with
-    /// a let binding or for loop.
+    /// ```example
+    /// #let queue = (1, 2, 3, 4, 5)
+    /// #queue.extract(calc.even) \
+    /// #queue
+    /// ```
     #[func]
-    pub fn enumerate(
-        &self,
-        /// The index returned for the first pair of the returned list.
-        #[named]
+    pub fn extract(
+        &mut self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The function to apply to each item. Must return a boolean.
+        test: Func,
+    ) -> SourceResult<Array> {
+        let mask = self.test_mask(vm, &test, "extract")?;
+        Ok(self.extract_by(mask))
+    }
+
+    /// Calls the given function with the items of the array as positional
+    /// arguments and returns its result.
+    ///
+    /// This is equivalent to `{f(..array)}`.
+    ///
+    /// ```example
+    /// #(2, 10).apply(calc.pow)
+    /// ```
+    #[func]
+    pub fn apply(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The function to call.
+        func: Func,
+    ) -> SourceResult<Value> {
+        func.call_in(vm, Args::new(func.span(), self.iter().cloned()), "apply")
+    }
+
+    /// Produces a new array in which all items from the original one were
+    /// transformed with the given function.
+    ///
+    /// The function is called with each item only. To also receive the
+    /// index, use [`map-indexed`]($array.map-indexed).
+    #[func]
+    pub fn map(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The function to apply to each item.
+        mapper: Func,
+    ) -> SourceResult<Array> {
+        // Each call consumes its arguments (closure calls are memoized by
+        // them), so the only allocation we can share is the output's.
+        let mut out = EcoVec::with_capacity(self.len());
+        for item in self.iter() {
+            let args = Args::new(mapper.span(), [item.clone()]);
+            out.push(mapper.call_in(vm, args, "map")?);
+        }
+        Ok(out.into())
+    }
+
+    /// Produces a new array in which all items from the original one were
+    /// transformed with the given function, which is also passed the index of
+    /// each item.
+    ///
+    /// Unlike with [`map`]($array.map), the function is always called with
+    /// two arguments: the index and the item.
+    ///
+    /// ```example
+    /// #("a", "b", "c").map-indexed((i, x) => x * (i + 1))
+    /// ```
+    #[func]
+    pub fn map_indexed(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The function to apply to each index and item.
+        mapper: Func,
+    ) -> SourceResult<Array> {
+        let mut out = EcoVec::with_capacity(self.len());
+        for (i, item) in self.iter().enumerate() {
+            let args = Args::new(mapper.span(), [Value::Int(i as i64), item.clone()]);
+            out.push(mapper.call_in(vm, args, "map-indexed")?);
+        }
+        Ok(out.into())
+    }
+
+    /// Transforms all items of the array with the given function in place.
+    /// This is the in-place counterpart to [`map`]($array.map) and avoids
+    /// allocating a second array.
+    ///
+    /// If the function fails for an item, the items before it keep their new
+    /// values while it and all following items are left unchanged.
+    ///
+    /// ```example
+    /// #let values = (1, 2, 3)
+    /// #values.map-in-place(x => x * 2)
+    /// #values
+    /// ```
+    #[func]
+    pub fn map_in_place(
+        &mut self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The function to apply to each item.
+        mapper: Func,
+    ) -> SourceResult<()> {
+        for item in self.0.make_mut() {
+            let args = Args::new(mapper.span(), [item.clone()]);
+            *item = mapper.call_in(vm, args, "map-in-place")?;
+        }
+        Ok(())
+    }
+
+    /// Returns a new array with the values alongside their indices.
+    ///
+    /// The returned array consists of `(index, value)` pairs in the form of
+    /// length-2 arrays. These can be [destructured]($scripting/#bindings) with
+    /// a let binding or for loop.
+    #[func]
+    pub fn enumerate(
+        &self,
+        /// The index returned for the first pair of the returned list.
+        #[named]
         #[default(0)]
         start: i64,
     ) -> StrResult<Array> {
@@ -486,30 +1113,120 @@ impl Array {
         }
 
         // If there is more than one array, we use the manual method.
-        let mut out = Self::with_capacity(self.len());
-        let mut iterators = args
-            .all::<Array>()?
-            .into_iter()
-            .map(|i| i.into_iter())
-            .collect::<Vec<_>>();
+        let others = args.all::<Array>()?;
         args.finish()?;
+        Ok(self.zip_all(others))
+    }
 
-        for this in self.iter() {
-            let mut row = Self::with_capacity(1 + iterators.len());
-            row.push(this.clone());
+    /// Combines the array with another one item by item. The given function is
+    /// called with each pair of corresponding items and the results are
+    /// collected into a new array. If the arrays are of unequal length, the
+    /// remaining items of the longer one are ignored.
+    ///
+    /// ```example
+    /// #(1, 2, 3).zip-with((10, 20, 30), (a, b) => a * b)
+    /// ```
+    #[func]
+    pub fn zip_with(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The array to combine with.
+        other: Array,
+        /// The function to combine the items with. Must have two parameters:
+        /// One for an item of this array and one for an item of the other.
+        combiner: Func,
+    ) -> SourceResult<Array> {
+        self.iter()
+            .zip(other)
+            .map(|(first, second)| {
+                let args = Args::new(combiner.span(), [first.clone(), second]);
+                combiner.call_in(vm, args, "zip-with")
+            })
+            .collect()
+    }
 
-            for iterator in &mut iterators {
-                let Some(item) = iterator.next() else {
-                    return Ok(out);
-                };
+    /// Treats the array as a matrix of rows and returns its transpose, that is,
+    /// an array of its columns. Fails with an error if an item is not an array
+    /// or if the rows are of unequal length.
+    ///
+    /// ```example
+    /// #((1, 2, 3), (4, 5, 6)).transpose()
+    /// ```
+    #[func]
+    pub fn transpose(&self) -> StrResult<Array> {
+        let rows = self
+            .iter()
+            .cloned()
+            .map(Value::cast::<Array>)
+            .collect::<StrResult<Vec<_>>>()?;
 
-                row.push(item);
-            }
+        let Some(width) = rows.first().map(Array::len) else {
+            return Ok(Array::new());
+        };
 
-            out.push(row.into_value());
+        if rows.iter().any(|row| row.len() != width) {
+            bail!("rows must have equal length");
+        }
+
+        Ok((0..width)
+            .map(|i| rows.iter().map(|row| row.0[i].clone()).collect::<Array>())
+            .map(Value::Array)
+            .collect())
+    }
+
+    /// Merges the array with another one by alternately taking items from
+    /// both. Once the shorter array runs out, the remaining items of the
+    /// longer one are appended.
+    ///
+    /// Unlike [`zip`]($array.zip), this produces a flat array.
+    ///
+    /// ```example
+    /// #("Q1", "Q2", "Q3").interleave(("A1", "A2"))
+    /// ```
+    #[func]
+    pub fn interleave(
+        &self,
+        /// The array to take every second item from.
+        other: Array,
+    ) -> Array {
+        let mut out = EcoVec::with_capacity(self.len() + other.len());
+        let mut a = self.iter().cloned();
+        let mut b = other.into_iter();
+        loop {
+            match (a.next(), b.next()) {
+                (None, None) => break,
+                (x, y) => out.extend(x.into_iter().chain(y)),
+            }
         }
+        out.into()
+    }
 
-        Ok(out)
+    /// Returns all pairs of an item from this array with an item from the
+    /// other array. The pairs are in the form of length-2 arrays and ordered
+    /// by this array's items first.
+    ///
+    /// ```example
+    /// #(1, 2).cartesian-product(("a", "b", "c"))
+    /// ```
+    #[func]
+    pub fn cartesian_product(
+        &self,
+        /// The array to pair this array's items with.
+        other: Array,
+    ) -> StrResult<Array> {
+        let count = self.len().checked_mul(other.len()).ok_or_else(|| {
+            eco_format!("cannot pair {} items with {} items", self.len(), other.len())
+        })?;
+        check_len(count)?;
+
+        let mut out = EcoVec::with_capacity(count);
+        for a in self.iter() {
+            for b in other.iter() {
+                out.push(array![a.clone(), b.clone()].into_value());
+            }
+        }
+        Ok(out.into())
     }
 
     /// Folds all items into a single value using an accumulator function.
@@ -527,11 +1244,236 @@ impl Array {
         let mut acc = init;
         for item in self.iter() {
             let args = Args::new(folder.span(), [acc, item.clone()]);
-            acc = folder.call_vm(vm, args)?;
+            acc = folder.call_in(vm, args, "fold")?;
+        }
+        Ok(acc)
+    }
+
+    /// Like [`fold`]($array.fold), but can stop before reaching the end of the
+    /// array.
+    ///
+    /// The folding function must return a dictionary with the new accumulated
+    /// `value` and optionally a boolean `done`. If `done` is `{true}`, folding
+    /// stops and `value` is returned without looking at the remaining items.
+    ///
+    /// ```example
+    /// // Sum up items until the total exceeds 10.
+    /// #(4, 3, 5, 6, 2).fold-while(0, (sum, x) => (
+    ///   value: sum + x,
+    ///   done: sum + x > 10,
+    /// ))
+    /// ```
+    #[func]
+    pub fn fold_while(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The initial value to start with.
+        init: Value,
+        /// The folding function. Must have two parameters: One for the
+        /// accumulated value and one for an item.
+        folder: Func,
+    ) -> SourceResult<Value> {
+        let mut acc = init;
+        for item in self.iter() {
+            let args = Args::new(folder.span(), [acc, item.clone()]);
+            let mut step = folder
+                .call_in(vm, args, "fold-while")?
+                .cast::<Dict>()
+                .at(folder.span())?;
+            let done = match step.take("done") {
+                Ok(done) => done.cast::<bool>().at(folder.span())?,
+                Err(_) => false,
+            };
+            acc = step.take("value").at(folder.span())?;
+            step.finish(&["value", "done"]).at(folder.span())?;
+            if done {
+                break;
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Like [`fold`]($array.fold), but returns all successive accumulated
+    /// values instead of just the final one. The returned array has the same
+    /// length as the original one.
+    ///
+    /// ```example
+    /// #(1, 2, 3, 4).scan(0, (s, x) => s + x)
+    /// ```
+    #[func]
+    pub fn scan(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The initial value to start with.
+        init: Value,
+        /// The folding function. Must have two parameters: One for the
+        /// accumulated value and one for an item.
+        folder: Func,
+    ) -> SourceResult<Array> {
+        let mut acc = init;
+        let mut out = EcoVec::with_capacity(self.len());
+        for item in self.iter() {
+            let args = Args::new(folder.span(), [acc, item.clone()]);
+            acc = folder.call_in(vm, args, "scan")?;
+            out.push(acc.clone());
+        }
+        Ok(out.into())
+    }
+
+    /// Folds all items into a single value, starting from the last item.
+    ///
+    /// Unlike with [`fold`]($array.fold), the folding function is called with
+    /// the item first and the accumulated value second. This is useful to
+    /// build right-nested structures.
+    ///
+    /// ```example
+    /// #(1, 2, 3).fold-right("end", (x, rest) => (x, rest))
+    /// ```
+    #[func]
+    pub fn fold_right(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The initial value to start with.
+        init: Value,
+        /// The folding function. Must have two parameters: One for an item and
+        /// one for the accumulated value.
+        folder: Func,
+    ) -> SourceResult<Value> {
+        let mut acc = init;
+        for item in self.iter().rev() {
+            let args = Args::new(folder.span(), [item.clone(), acc]);
+            acc = folder.call_in(vm, args, "fold-right")?;
         }
         Ok(acc)
     }
 
+    /// Like [`fold-right`]($array.fold-right), but returns all successive
+    /// accumulated values instead of just the final one. The value at each
+    /// index is the result of folding the items from that index to the end.
+    ///
+    /// ```example
+    /// #(1, 2, 3, 4).scan-right(0, (x, s) => x + s)
+    /// ```
+    #[func]
+    pub fn scan_right(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The initial value to start with.
+        init: Value,
+        /// The folding function. Must have two parameters: One for an item and
+        /// one for the accumulated value.
+        folder: Func,
+    ) -> SourceResult<Array> {
+        let mut acc = init;
+        let mut out = EcoVec::with_capacity(self.len());
+        for item in self.iter().rev() {
+            let args = Args::new(folder.span(), [item.clone(), acc]);
+            acc = folder.call_in(vm, args, "scan-right")?;
+            out.push(acc.clone());
+        }
+        out.make_mut().reverse();
+        Ok(out.into())
+    }
+
+    /// Like [`fold-right`]($array.fold-right), but uses the last item as the
+    /// initial value. Returns `{none}` if the array is empty.
+    ///
+    /// ```example
+    /// #(1, 2, 3).reduce-right((x, rest) => (x, rest)) \
+    /// #(2, 3, 2).reduce-right(calc.pow)
+    /// ```
+    #[func]
+    pub fn reduce_right(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The reducing function. Must have two parameters: One for an item
+        /// and one for the accumulated value.
+        reducer: Func,
+    ) -> SourceResult<Value> {
+        let mut iter = self.iter().rev();
+        let Some(mut acc) = iter.next().cloned() else {
+            return Ok(Value::None);
+        };
+
+        for item in iter {
+            let args = Args::new(reducer.span(), [item.clone(), acc]);
+            acc = reducer.call_in(vm, args, "reduce-right")?;
+        }
+
+        Ok(acc)
+    }
+
+    /// Transforms each window of consecutive items with the given function.
+    ///
+    /// The function is called with each run of `size` consecutive items as an
+    /// array. Returns one result per window. If the array has fewer items than
+    /// the window size, the result is empty.
+    ///
+    /// ```example
+    /// #(1, 4, 2, 8).windows-map(2, w => w.at(1) - w.at(0))
+    /// ```
+    #[func]
+    pub fn windows_map(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The number of items in each window. Must be positive.
+        size: NonZeroUsize,
+        /// The function to apply to each window.
+        mapper: Func,
+    ) -> SourceResult<Array> {
+        let size = size.get();
+        if self.len() < size {
+            return Ok(Array::new());
+        }
+
+        let mut out = EcoVec::with_capacity(self.len() - size + 1);
+        for window in self.0.windows(size) {
+            let args = Args::new(mapper.span(), [Value::Array(window.into())]);
+            out.push(mapper.call_in(vm, args, "windows-map")?);
+        }
+        Ok(out.into())
+    }
+
+    /// Reduces each window of consecutive items with the given function.
+    ///
+    /// For each run of `size` consecutive items, the items are combined from
+    /// left to right with the reducer, like with [`fold`]($array.fold) without
+    /// an initial value. Returns one result per window. If the array has fewer
+    /// items than the window size, the result is empty.
+    ///
+    /// ```example
+    /// #(1, 4, 2, 8, 5).sliding-reduce(2, calc.max) \
+    /// #(1, 2, 3, 4).sliding-reduce(3, (a, b) => a + b)
+    /// ```
+    #[func]
+    pub fn sliding_reduce(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The number of items in each window. Must be positive.
+        size: NonZeroUsize,
+        /// The reducing function. Must have two parameters: One for the
+        /// accumulated value and one for an item.
+        reducer: Func,
+    ) -> SourceResult<Array> {
+        let mut out = EcoVec::new();
+        for window in self.0.windows(size.get()) {
+            let mut acc = window[0].clone();
+            for item in &window[1..] {
+                let args = Args::new(reducer.span(), [acc, item.clone()]);
+                acc = reducer.call_in(vm, args, "sliding-reduce")?;
+            }
+            out.push(acc);
+        }
+        Ok(out.into())
+    }
+
     /// Sums all items (works for all types that can be added).
     #[func]
     pub fn sum(
@@ -575,88 +1517,481 @@ impl Array {
         Ok(acc)
     }
 
+    /// Sums the results of applying a function to all items.
+    ///
+    /// ```example
+    /// #let items = ((price: 3), (price: 5))
+    /// #items.sum-by(item => item.price)
+    /// ```
+    #[func]
+    pub fn sum_by(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The callsite span.
+        span: Span,
+        /// The function to apply to each item before summing.
+        mapper: Func,
+        /// What to return if the array is empty. Must be set if the array can
+        /// be empty.
+        #[named]
+        default: Option<Value>,
+    ) -> SourceResult<Value> {
+        self.fold_by(vm, span, &mapper, "sum-by", default, add)?
+            .ok_or("cannot calculate sum of empty array with no default")
+            .at(span)
+    }
+
+    /// Calculates the product of the results of applying a function to all
+    /// items.
+    ///
+    /// ```example
+    /// #((1, 2), (3, 4)).product-by(pair => pair.at(1))
+    /// ```
+    #[func]
+    pub fn product_by(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The callsite span.
+        span: Span,
+        /// The function to apply to each item before multiplying.
+        mapper: Func,
+        /// What to return if the array is empty. Must be set if the array can
+        /// be empty.
+        #[named]
+        default: Option<Value>,
+    ) -> SourceResult<Value> {
+        self.fold_by(vm, span, &mapper, "product-by", default, mul)?
+            .ok_or("cannot calculate product of empty array with no default")
+            .at(span)
+    }
+
     /// Whether the given function returns `{true}` for any item in the array.
     #[func]
-    pub fn any(
+    pub fn any(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The function to apply to each item. Must return a boolean.
+        test: Func,
+    ) -> SourceResult<bool> {
+        for item in self.iter() {
+            let args = Args::new(test.span(), [item.clone()]);
+            if test.call_in(vm, args, "any")?.cast::<bool>().at(test.span())? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Whether the given function returns `{true}` for all items in the array.
+    #[func]
+    pub fn all(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The function to apply to each item. Must return a boolean.
+        test: Func,
+    ) -> SourceResult<bool> {
+        for item in self.iter() {
+            let args = Args::new(test.span(), [item.clone()]);
+            if !test.call_in(vm, args, "all")?.cast::<bool>().at(test.span())? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Combine all nested arrays into a single flat one.
+    #[func]
+    pub fn flatten(&self) -> Array {
+        self.flatten_depth(-1)
+    }
+
+    /// Combine nested arrays up to the given depth into a single array.
+    ///
+    /// ```example
+    /// #(1, (2, (3, (4,)))).flatten-depth(1) \
+    /// #(1, (2, (3, (4,)))).flatten-depth(2)
+    /// ```
+    #[func]
+    pub fn flatten_depth(
+        &self,
+        /// How many levels of nesting to remove. If negative, all levels are
+        /// removed, like with [`flatten`]($array.flatten).
+        depth: i64,
+    ) -> Array {
+        let depth = usize::try_from(depth).unwrap_or(usize::MAX);
+
+        // We use an explicit stack instead of recursion so that deeply nested
+        // arrays cannot overflow the native stack.
+        let mut flat = EcoVec::with_capacity(self.0.len());
+        let mut stack = vec![self.iter()];
+        while let Some(iter) = stack.last_mut() {
+            match iter.next() {
+                Some(Value::Array(nested)) if stack.len() <= depth => {
+                    stack.push(nested.iter())
+                }
+                Some(item) => flat.push(item.clone()),
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+        flat.into()
+    }
+
+    /// Return a new array with the same items, but in reverse order.
+    #[func(title = "Reverse")]
+    pub fn rev(&self) -> Array {
+        self.0.iter().cloned().rev().collect()
+    }
+
+    /// Returns a new array with every `n`-th item, starting with the first
+    /// one.
+    ///
+    /// ```example
+    /// #range(10).step-by(3)
+    /// ```
+    #[func]
+    pub fn step_by(
+        &self,
+        /// The distance between the picked items. Must be positive.
+        n: NonZeroUsize,
+    ) -> Array {
+        self.iter().step_by(n.get()).cloned().collect()
+    }
+
+    /// Returns all windows of consecutive items of the given size.
+    ///
+    /// By default, each window starts one item after the previous one. With a
+    /// larger `step`, windows overlap less or, if the step is larger than the
+    /// size, leave out items in between. If the array has fewer items than the
+    /// window size, the result is empty.
+    ///
+    /// ```example
+    /// #range(5).windows(3) \
+    /// #range(7).windows(3, step: 2)
+    /// ```
+    #[func]
+    pub fn windows(
+        &self,
+        /// The number of items in each window. Must be positive.
+        size: NonZeroUsize,
+        /// The distance between the starts of neighbouring windows. Must be
+        /// positive.
+        #[named]
+        #[default(NonZeroUsize::ONE)]
+        step: NonZeroUsize,
+    ) -> Array {
+        let size = size.get();
+        let count = self.len().checked_sub(size).map_or(0, |n| n + 1);
+        (0..count)
+            .step_by(step.get())
+            .map(|start| Value::Array(self.0[start..start + size].into()))
+            .collect()
+    }
+
+    /// Splits the array into chunks of the given size.
+    ///
+    /// If the length of the array is not a multiple of the chunk size, the
+    /// last chunk holds the remaining items and is shorter than the others.
+    /// Returns an array of the chunks, each of which is an array itself.
+    ///
+    /// ```example
+    /// #range(7).chunks(3)
+    /// ```
+    #[func]
+    pub fn chunks(
+        &self,
+        /// The number of items in each chunk. Must be positive.
+        chunk_size: NonZeroUsize,
+    ) -> Array {
+        self.as_slice()
+            .chunks(chunk_size.get())
+            .map(|chunk| Value::Array(chunk.into()))
+            .collect()
+    }
+
+    /// Splits the array into chunks of exactly the given size.
+    ///
+    /// Unlike [`chunks`]($array.chunks), the remaining items at the end that
+    /// don't fill a complete chunk are dropped. Returns an array of the
+    /// chunks, each of which is an array itself.
+    ///
+    /// ```example
+    /// #range(7).chunks-exact(3)
+    /// ```
+    #[func]
+    pub fn chunks_exact(
+        &self,
+        /// The number of items in each chunk. Must be positive.
+        chunk_size: NonZeroUsize,
+    ) -> Array {
+        self.as_slice()
+            .chunks_exact(chunk_size.get())
+            .map(|chunk| Value::Array(chunk.into()))
+            .collect()
+    }
+
+    /// Splits the array into chunks of the given size, counted from the end.
+    ///
+    /// Unlike with [`chunks`]($array.chunks), if the length of the array is
+    /// not a multiple of the chunk size, the first chunk holds the remaining
+    /// items and is shorter than the others.
+    /// Returns an array of the chunks, each of which is an array itself.
+    ///
+    /// ```example
+    /// #range(7).rchunks(3) \
+    /// #"1234567".clusters().rchunks(3).map(c => c.join()).join(",")
+    /// ```
+    #[func]
+    pub fn rchunks(
+        &self,
+        /// The number of items in each chunk. Must be positive.
+        chunk_size: NonZeroUsize,
+    ) -> Array {
+        self.as_slice()
+            .rchunks(chunk_size.get())
+            .rev()
+            .map(|chunk| Value::Array(chunk.into()))
+            .collect()
+    }
+
+    /// Splits the array into the given number of chunks whose lengths differ
+    /// by at most one. The earlier chunks receive the extra items. If the
+    /// array has fewer items than chunks are requested, the trailing chunks
+    /// are empty.
+    ///
+    /// ```example
+    /// #range(7).chunk-evenly(3)
+    /// ```
+    #[func]
+    pub fn chunk_evenly(
+        &self,
+        /// The number of chunks. Must be positive.
+        groups: NonZeroUsize,
+    ) -> Array {
+        let groups = groups.get();
+        let (base, extra) = (self.len() / groups, self.len() % groups);
+        let mut start = 0;
+        (0..groups)
+            .map(|i| {
+                let end = start + base + (i < extra) as usize;
+                let chunk = Value::Array(self.0[start..end].into());
+                start = end;
+                chunk
+            })
+            .collect()
+    }
+
+    /// Pads the array at the end with copies of a value until it reaches the
+    /// given length. Returns the array unchanged if it is already at least as
+    /// long.
+    ///
+    /// ```example
+    /// #(1, 2).pad-end(4, 0)
+    /// ```
+    #[func]
+    pub fn pad_end(
+        &self,
+        /// The length to pad the array to.
+        len: usize,
+        /// The value to pad with.
+        fill: Value,
+    ) -> StrResult<Array> {
+        let missing = len.saturating_sub(self.len());
+        if missing > 0 {
+            check_len(len)?;
+        }
+        Ok(self
+            .iter()
+            .cloned()
+            .chain(std::iter::repeat(fill).take(missing))
+            .collect())
+    }
+
+    /// Pads the array at the start with copies of a value until it reaches
+    /// the given length. Returns the array unchanged if it is already at least
+    /// as long.
+    ///
+    /// ```example
+    /// #(1, 2).pad-start(4, 0)
+    /// ```
+    #[func]
+    pub fn pad_start(
+        &self,
+        /// The length to pad the array to.
+        len: usize,
+        /// The value to pad with.
+        fill: Value,
+    ) -> StrResult<Array> {
+        let missing = len.saturating_sub(self.len());
+        if missing > 0 {
+            check_len(len)?;
+        }
+        Ok(std::iter::repeat(fill)
+            .take(missing)
+            .chain(self.iter().cloned())
+            .collect())
+    }
+
+    /// Split the array at occurrences of the specified value.
+    ///
+    /// Consecutive occurrences, as well as occurrences at the start or end of
+    /// the array, produce empty subarrays.
+    ///
+    /// ```example
+    /// #(1, 2, none, 3, none, none, 4).split(none)
+    /// ```
+    #[func]
+    pub fn split(
+        &self,
+        /// The value to split at.
+        at: Value,
+    ) -> Array {
+        self.as_slice()
+            .split(|value| *value == at)
+            .map(|subslice| Value::Array(subslice.iter().cloned().collect()))
+            .collect()
+    }
+
+    /// Splits the array into runs of consecutive items.
+    ///
+    /// The given function is called with each pair of neighbouring items. A
+    /// new run is started whenever it returns `{false}`. Returns an array of
+    /// the runs, each of which is an array itself.
+    ///
+    /// ```example
+    /// #(1, 2, 3, 7, 8, 10).chunk-by((a, b) => b == a + 1)
+    /// ```
+    #[func]
+    pub fn chunk_by(
         &self,
         /// The virtual machine.
         vm: &mut Vm,
-        /// The function to apply to each item. Must return a boolean.
+        /// The function deciding whether two neighbouring items belong to
+        /// the same run. Must have two parameters and return a boolean.
         test: Func,
-    ) -> SourceResult<bool> {
+    ) -> SourceResult<Array> {
+        let mut out = EcoVec::new();
+        let mut run: EcoVec<Value> = EcoVec::new();
         for item in self.iter() {
-            let args = Args::new(test.span(), [item.clone()]);
-            if test.call_vm(vm, args)?.cast::<bool>().at(test.span())? {
-                return Ok(true);
+            if let Some(prev) = run.last() {
+                let args = Args::new(test.span(), [prev.clone(), item.clone()]);
+                if !test.call_in(vm, args, "chunk-by")?.cast::<bool>().at(test.span())? {
+                    out.push(Value::Array(std::mem::take(&mut run).into()));
+                }
             }
+            run.push(item.clone());
         }
-
-        Ok(false)
+        if !run.is_empty() {
+            out.push(Value::Array(run.into()));
+        }
+        Ok(out.into())
     }
 
-    /// Whether the given function returns `{true}` for all items in the array.
+    /// Reduces runs of consecutive items that share a key.
+    ///
+    /// Walks the array and starts a new segment whenever the key of an item
+    /// differs from the key of the previous one. The items of each segment are
+    /// then combined from left to right with the reducer, like with
+    /// [`fold`]($array.fold) without an initial value. Returns one result per
+    /// segment. Since only neighbouring items are compared, the array should
+    /// already be ordered by the key.
+    ///
+    /// ```example
+    /// #let rows = (("a", 1), ("a", 2), ("b", 5))
+    /// #rows.segment-reduce(
+    ///   row => row.at(0),
+    ///   (acc, row) => (acc.at(0), acc.at(1) + row.at(1)),
+    /// )
+    /// ```
     #[func]
-    pub fn all(
+    pub fn segment_reduce(
         &self,
         /// The virtual machine.
         vm: &mut Vm,
-        /// The function to apply to each item. Must return a boolean.
-        test: Func,
-    ) -> SourceResult<bool> {
-        for item in self.iter() {
-            let args = Args::new(test.span(), [item.clone()]);
-            if !test.call_vm(vm, args)?.cast::<bool>().at(test.span())? {
-                return Ok(false);
-            }
-        }
-
-        Ok(true)
-    }
-
-    /// Combine all nested arrays into a single flat one.
-    #[func]
-    pub fn flatten(&self) -> Array {
-        let mut flat = EcoVec::with_capacity(self.0.len());
+        /// The function to apply to each item to determine its key.
+        key: Func,
+        /// The reducing function. Must have two parameters: One for the
+        /// accumulated value and one for an item.
+        reducer: Func,
+    ) -> SourceResult<Array> {
+        let mut out = EcoVec::new();
+        let mut segment: Option<(Value, Value)> = None;
         for item in self.iter() {
-            if let Value::Array(nested) = item {
-                flat.extend(nested.flatten().into_iter());
-            } else {
-                flat.push(item.clone());
-            }
+            let args = Args::new(key.span(), [item.clone()]);
+            let k = key.call_in(vm, args, "segment-reduce")?;
+            segment = Some(match segment {
+                Some((prev, acc)) if ops::equal(&prev, &k) => {
+                    let args = Args::new(reducer.span(), [acc, item.clone()]);
+                    (prev, reducer.call_in(vm, args, "segment-reduce")?)
+                }
+                Some((_, acc)) => {
+                    out.push(acc);
+                    (k, item.clone())
+                }
+                None => (k, item.clone()),
+            });
         }
-        flat.into()
-    }
-
-    /// Return a new array with the same items, but in reverse order.
-    #[func(title = "Reverse")]
-    pub fn rev(&self) -> Array {
-        self.0.iter().cloned().rev().collect()
+        out.extend(segment.map(|(_, acc)| acc));
+        Ok(out.into())
     }
 
-    /// Split the array at occurrences of the specified value.
+    /// Combines all items into a single content sequence. Fails with an error
+    /// if an item is not content.
+    ///
+    /// Unlike [`join`]($array.join), which merges the items one by one, this
+    /// keeps each item as a separate child of the resulting sequence.
+    ///
+    /// ```example
+    /// #([*A*], [_B_], [C]).sequence()
+    /// ```
     #[func]
-    pub fn split(
-        &self,
-        /// The value to split at.
-        at: Value,
-    ) -> Array {
-        self.as_slice()
-            .split(|value| *value == at)
-            .map(|subslice| Value::Array(subslice.iter().cloned().collect()))
-            .collect()
+    pub fn sequence(&self) -> StrResult<Content> {
+        let children = self
+            .iter()
+            .enumerate()
+            .map(|(i, value)| match value {
+                Value::Content(content) => Ok(content.clone()),
+                other => bail!("expected content, found {} (at index {i})", other.ty()),
+            })
+            .collect::<StrResult<Vec<_>>>()?;
+        Ok(Content::sequence(children))
     }
 
     /// Combine all items in the array into one.
+    ///
+    /// Items are joined like in a code block: strings and content are
+    /// concatenated, arrays are concatenated, and dictionaries are merged,
+    /// with later keys overriding earlier ones.
+    ///
+    /// If the separator is a function, it is called with the two neighbouring
+    /// items and its return value is inserted between them.
+    ///
+    /// ```example
+    /// #(1, 2, 5, 6).map(str).join(
+    ///   (a, b) => if int(b) - int(a) > 1 [ --- ] else [, ]
+    /// )
+    /// ```
     #[func]
     pub fn join(
         &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The callsite span.
+        span: Span,
         /// A value to insert between each item of the array.
         #[default]
         separator: Option<Value>,
         /// An alternative separator between the last two items.
         #[named]
         last: Option<Value>,
-    ) -> StrResult<Value> {
+    ) -> SourceResult<Value> {
         let len = self.0.len();
         let separator = separator.unwrap_or(Value::None);
 
@@ -664,14 +1999,25 @@ impl Array {
         let mut result = Value::None;
         for (i, value) in self.iter().cloned().enumerate() {
             if i > 0 {
-                if i + 1 == len && last.is_some() {
-                    result = ops::join(result, last.take().unwrap())?;
+                let sep = if i + 1 == len && last.is_some() {
+                    last.take().unwrap()
                 } else {
-                    result = ops::join(result, separator.clone())?;
-                }
+                    separator.clone()
+                };
+
+                let sep = match sep {
+                    Value::Func(func) => {
+                        let prev = self.0[i - 1].clone();
+                        let args = Args::new(func.span(), [prev, value.clone()]);
+                        func.call_in(vm, args, "join")?
+                    }
+                    sep => sep,
+                };
+
+                result = ops::join(result, sep).at(span)?;
             }
 
-            result = ops::join(result, value)?;
+            result = ops::join(result, value).at(span)?;
         }
 
         Ok(result)
@@ -710,7 +2056,8 @@ impl Array {
     /// function. The sorting algorithm used is stable.
     ///
     /// Returns an error if two values could not be compared or if the key
-    /// function (if given) yields an error.
+    /// function (if given) yields an error. Not-a-number floats are sorted
+    /// after all other numbers.
     #[func]
     pub fn sorted(
         &self,
@@ -728,20 +2075,18 @@ impl Array {
         let mut key_of = |x: Value| match &key {
             // NOTE: We are relying on `comemo`'s memoization of function
             // evaluation to not excessively reevaluate the `key`.
-            Some(f) => f.call_vm(vm, Args::new(f.span(), [x])),
+            Some(f) => f.call_in(vm, Args::new(f.span(), [x]), "sorted"),
             None => Ok(x),
         };
         vec.make_mut().sort_by(|a, b| {
             // Until we get `try` blocks :)
             match (key_of(a.clone()), key_of(b.clone())) {
-                (Ok(a), Ok(b)) => {
-                    typst::eval::ops::compare(&a, &b).unwrap_or_else(|err| {
-                        if result.is_ok() {
-                            result = Err(err).at(span);
-                        }
-                        Ordering::Equal
-                    })
-                }
+                (Ok(a), Ok(b)) => sort_cmp(&a, &b).unwrap_or_else(|err| {
+                    if result.is_ok() {
+                        result = Err(err).at(span);
+                    }
+                    Ordering::Equal
+                }),
                 (Err(e), _) | (_, Err(e)) => {
                     if result.is_ok() {
                         result = Err(e);
@@ -753,6 +2098,78 @@ impl Array {
         result.map(|_| vec.into())
     }
 
+    /// Returns the `n` largest items of the array in descending order. If the
+    /// array has fewer items, all of them are returned. Items are compared as
+    /// with [`sorted`]($array.sorted) and equal items keep their order.
+    ///
+    /// ```example
+    /// #(3, 7, 1, 9, 4).max-n(3)
+    /// ```
+    #[func(title = "Maximum N")]
+    pub fn max_n(
+        &self,
+        /// How many items to return.
+        n: usize,
+    ) -> StrResult<Array> {
+        self.select(n, |a, b| sort_cmp(b, a))
+    }
+
+    /// Returns the `n` smallest items of the array in ascending order. If the
+    /// array has fewer items, all of them are returned. Items are compared as
+    /// with [`sorted`]($array.sorted) and equal items keep their order.
+    ///
+    /// ```example
+    /// #(3, 7, 1, 9, 4).min-n(3)
+    /// ```
+    #[func(title = "Minimum N")]
+    pub fn min_n(
+        &self,
+        /// How many items to return.
+        n: usize,
+    ) -> StrResult<Array> {
+        self.select(n, sort_cmp)
+    }
+
+    /// Whether the array is sorted in ascending order, in the order used by
+    /// [`sorted`]($array.sorted). Fails with an error if two neighbouring
+    /// items cannot be compared.
+    ///
+    /// ```example
+    /// #(1, 2, 2, 3).is-sorted() \
+    /// #(1, 3, 2).is-sorted()
+    /// ```
+    #[func]
+    pub fn is_sorted(&self) -> StrResult<bool> {
+        for pair in self.0.windows(2) {
+            if sort_cmp(&pair[0], &pair[1])? == Ordering::Greater {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Return a sorted version of this array that never fails, even if the
+    /// array contains values that cannot be compared. The sorting algorithm
+    /// used is stable.
+    ///
+    /// Values are first grouped by type, in this order: `{none}`, `{auto}`,
+    /// booleans, numbers (integers and floats together), strings, and then all
+    /// other types ordered by their name. Within a group, values are ordered
+    /// like with [`sorted`]($array.sorted). Values of the same type that
+    /// cannot be compared with each other are ordered by kind first (e.g.
+    /// lengths in `pt` before lengths in `em` before mixed ones) and then by
+    /// their representation.
+    ///
+    /// ```example
+    /// #(2, "b", none, 1.5, "a", true).sorted-total()
+    /// ```
+    #[func]
+    pub fn sorted_total(&self) -> Array {
+        let mut vec = self.0.clone();
+        vec.make_mut().sort_by(total_cmp);
+        vec.into()
+    }
+
     /// Deduplicates all items in the array.
     ///
     /// Returns a new array with all duplicate items removed. Only the first
@@ -775,7 +2192,7 @@ impl Array {
         let mut key_of = |x: Value| match &key {
             // NOTE: We are relying on `comemo`'s memoization of function
             // evaluation to not excessively reevaluate the `key`.
-            Some(f) => f.call_vm(vm, Args::new(f.span(), [x])),
+            Some(f) => f.call_in(vm, Args::new(f.span(), [x]), "dedup"),
             None => Ok(x),
         };
 
@@ -800,6 +2217,173 @@ impl Array {
 
         Ok(Self(out))
     }
+
+    /// Counts the distinct items in the array. Items are compared in the same
+    /// way as with [`dedup`]($array.dedup), so this is the same as
+    /// `{array.dedup().len()}`, but without building the deduplicated array.
+    ///
+    /// ```example
+    /// #(1, 1, 2, 3, 1).count-distinct()
+    /// ```
+    #[func]
+    pub fn count_distinct(&self) -> i64 {
+        // Like in `dedup`, we can't use a `HashSet` here because values that
+        // compare equal don't necessarily hash the same.
+        let mut seen: Vec<&Value> = vec![];
+        for value in self.iter() {
+            if !seen.iter().any(|other| typst::eval::ops::equal(value, other)) {
+                seen.push(value);
+            }
+        }
+        seen.len() as i64
+    }
+
+    /// Collapses runs of consecutive items with equal keys.
+    ///
+    /// Returns a new array in which each run of adjacent items whose keys are
+    /// equal is replaced by its first item. Unlike [`dedup`]($array.dedup),
+    /// only neighbouring items are compared.
+    ///
+    /// ```example
+    /// #(1, -1, 2, 3, -3, 1).dedup-by-key(calc.abs)
+    /// ```
+    #[func(title = "Deduplicate By Key")]
+    pub fn dedup_by_key(
+        &self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The function to apply to each item to determine its key.
+        key: Func,
+    ) -> SourceResult<Array> {
+        let mut out = EcoVec::with_capacity(self.0.len());
+        let mut last = None;
+        for value in self.iter() {
+            let k =
+                key.call_in(vm, Args::new(key.span(), [value.clone()]), "dedup-by-key")?;
+            if !last.as_ref().is_some_and(|prev| ops::equal(prev, &k)) {
+                out.push(value.clone());
+                last = Some(k);
+            }
+        }
+        Ok(Self(out))
+    }
+
+    /// Encodes runs of equal consecutive items.
+    ///
+    /// Returns an array of `(value, count)` pairs, one for each maximal run
+    /// of equal neighbouring items.
+    ///
+    /// ```example
+    /// #("a", "a", "b", "a", "a", "a").run-length()
+    /// ```
+    #[func]
+    pub fn run_length(&self) -> Array {
+        let mut runs: Vec<(Value, i64)> = vec![];
+        for item in self.iter() {
+            match runs.last_mut() {
+                Some((value, count)) if value == item => *count += 1,
+                _ => runs.push((item.clone(), 1)),
+            }
+        }
+        runs.into_iter()
+            .map(|(value, count)| array![value, count].into_value())
+            .collect()
+    }
+
+    /// Counts how often each string occurs in the array.
+    ///
+    /// Returns a dictionary that maps each distinct string to its number of
+    /// occurrences, in the order of first appearance. Fails with an error if
+    /// the array contains anything other than strings.
+    ///
+    /// ```example
+    /// #("a", "b", "a", "c", "a").tally()
+    /// ```
+    #[func]
+    pub fn tally(&self) -> StrResult<Dict> {
+        let mut counts: IndexMap<Str, i64> = IndexMap::new();
+        for item in self.iter() {
+            *counts.entry(item.clone().cast::<Str>()?).or_default() += 1;
+        }
+        Ok(counts
+            .into_iter()
+            .map(|(key, count)| (key, Value::Int(count)))
+            .collect())
+    }
+
+    /// Picks a pseudo-random item from the array. Fails with an error if the
+    /// array is empty.
+    ///
+    /// Typst documents must compile deterministically, so the item is picked
+    /// based on an explicit seed: The same seed always yields the same item.
+    ///
+    /// ```example
+    /// #("A", "B", "C", "D").sample(7)
+    /// ```
+    #[func]
+    pub fn sample(
+        &self,
+        /// The seed to derive the pick from.
+        seed: u64,
+    ) -> StrResult<Value> {
+        if self.is_empty() {
+            return Err(array_is_empty());
+        }
+
+        let mut rng = SplitMix64::new(seed);
+        Ok(self.0[rng.below(self.len())].clone())
+    }
+
+    /// Returns a new array with the same items, but in a pseudo-random order.
+    ///
+    /// Like with [`sample`]($array.sample), the order is derived from an
+    /// explicit seed: The same seed and array always yield the same order.
+    ///
+    /// ```example
+    /// #range(5).shuffle(1)
+    /// ```
+    #[func]
+    pub fn shuffle(
+        &self,
+        /// The seed to derive the order from.
+        seed: u64,
+    ) -> Array {
+        let mut vec = self.0.clone();
+        let slice = vec.make_mut();
+        let mut rng = SplitMix64::new(seed);
+        for i in (1..slice.len()).rev() {
+            slice.swap(i, rng.below(i + 1));
+        }
+        vec.into()
+    }
+}
+
+/// A small seeded pseudo-random number generator (SplitMix64).
+///
+/// We don't pull in a full random crate because all we need are cheap,
+/// reproducible picks.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// Create a new generator from a seed.
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Produce the next pseudo-random number.
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Produce a pseudo-random number in `0..bound`. The bound must not be
+    /// zero.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
 }
 
 /// A value that can be cast to bytes.
@@ -812,12 +2396,74 @@ cast! {
     v: Version => Self(v.values().iter().map(|&v| Value::Int(v as i64)).collect())
 }
 
+/// One or multiple indices into an array.
+pub enum ArrayIndex {
+    /// A single index.
+    Single(i64),
+    /// Multiple indices, for instance from a range.
+    Many(Vec<i64>),
+}
+
+cast! {
+    ArrayIndex,
+    v: i64 => Self::Single(v),
+    v: Vec<i64> => Self::Many(v),
+}
+
+/// Decides which items are matched by [`find`](Array::find) and similar
+/// methods.
+pub enum Searcher {
+    /// A function that returns whether an item matches.
+    Func(Func),
+    /// A regular expression that string items must match.
+    Regex(Regex),
+}
+
+impl Searcher {
+    /// Whether the item matches. Items that are not strings never match a
+    /// regular expression.
+    fn matches(
+        &self,
+        vm: &mut Vm,
+        item: &Value,
+        method: &'static str,
+    ) -> SourceResult<bool> {
+        match self {
+            Self::Func(func) => {
+                let args = Args::new(func.span(), [item.clone()]);
+                func.call_in(vm, args, method)?.cast::<bool>().at(func.span())
+            }
+            Self::Regex(regex) => {
+                Ok(matches!(item, Value::Str(text) if regex.is_match(text)))
+            }
+        }
+    }
+}
+
+cast! {
+    Searcher,
+    v: Func => Self::Func(v),
+    v: Regex => Self::Regex(v),
+}
+
 impl Debug for Array {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         f.debug_list().entries(&self.0).finish()
     }
 }
 
+impl PartialEq for Array {
+    fn eq(&self, other: &Self) -> bool {
+        // Arrays are cheaply cloned, so shared storage is common. Two arrays
+        // backed by the same allocation are considered equal without looking
+        // at the items. Like other languages with an identity shortcut, this
+        // means that an array containing `nan` equals its clones, but not a
+        // separately built array with the same items.
+        (std::ptr::eq(self.0.as_ptr(), other.0.as_ptr()) && self.len() == other.len())
+            || self.0 == other.0
+    }
+}
+
 impl Repr for Array {
     fn repr(&self) -> EcoString {
         let max = 40;
@@ -916,6 +2562,152 @@ impl<T: FromValue> FromValue for Vec<T> {
     }
 }
 
+/// Compare two values for `sorted`, placing NaN after all other numbers.
+fn sort_cmp(a: &Value, b: &Value) -> StrResult<Ordering> {
+    let is_nan = |v: &Value| matches!(v, Value::Float(f) if f.is_nan());
+    let is_num = |v: &Value| matches!(v, Value::Int(_) | Value::Float(_));
+    match (is_nan(a), is_nan(b)) {
+        (true, true) => Ok(Ordering::Equal),
+        (true, false) if is_num(b) => Ok(Ordering::Greater),
+        (false, true) if is_num(a) => Ok(Ordering::Less),
+        _ => ops::compare(a, b),
+    }
+}
+
+/// Compare two values in the total order used by `sorted-total`.
+fn total_cmp(a: &Value, b: &Value) -> Ordering {
+    fn group(value: &Value) -> (u8, &'static str) {
+        match value {
+            Value::None => (0, ""),
+            Value::Auto => (1, ""),
+            Value::Bool(_) => (2, ""),
+            Value::Int(_) | Value::Float(_) => (3, ""),
+            Value::Str(_) => (4, ""),
+            v => (5, v.ty().short_name()),
+        }
+    }
+
+    group(a).cmp(&group(b)).then_with(|| match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Int(a), Value::Int(b)) => a.cmp(b),
+        (Value::Int(a), Value::Float(b)) => int_float_cmp(*a, *b),
+        (Value::Float(a), Value::Int(b)) => int_float_cmp(*b, *a).reverse(),
+        (Value::Float(a), Value::Float(b)) => float_cmp(*a, *b),
+        (Value::Str(a), Value::Str(b)) => a.cmp(b),
+        _ => comparability(a)
+            .cmp(&comparability(b))
+            .then_with(|| ops::compare(a, b).unwrap_or_else(|_| a.repr().cmp(&b.repr()))),
+    })
+}
+
+/// Compare two floats for `sorted-total`.
+///
+/// Negative NaNs come before and positive NaNs after all other numbers. Both
+/// zeros are equal, just like an integer zero is equal to both of them.
+fn float_cmp(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b).unwrap_or_else(|| a.total_cmp(&b))
+}
+
+/// Compare an integer with a float exactly for `sorted-total`.
+///
+/// Converting the integer to a float would round it above 2^53, which would
+/// make the order intransitive.
+fn int_float_cmp(a: i64, b: f64) -> Ordering {
+    // The bounds of `i64` as floats. The upper bound itself is out of range.
+    const MIN: f64 = i64::MIN as f64;
+    const MAX: f64 = -MIN;
+
+    if b.is_nan() {
+        if b.is_sign_negative() {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }
+    } else if b >= MAX {
+        Ordering::Less
+    } else if b < MIN {
+        Ordering::Greater
+    } else {
+        // The integral part is exactly representable as an `i64` here.
+        let int = b.trunc();
+        a.cmp(&(int as i64))
+            .then_with(|| 0.0_f64.partial_cmp(&(b - int)).unwrap_or(Ordering::Equal))
+    }
+}
+
+/// Split the values of a type that is only partially comparable into classes
+/// of values that are either all comparable with each other or not
+/// comparable at all. This keeps the order of `sorted-total` transitive.
+fn comparability(value: &Value) -> u8 {
+    fn length(length: &Length) -> u8 {
+        if length.em.is_zero() {
+            0
+        } else if length.abs.is_zero() {
+            1
+        } else {
+            2
+        }
+    }
+
+    match value {
+        Value::Length(v) => length(v),
+        Value::Relative(v) if v.rel.is_zero() => length(&v.abs),
+        Value::Relative(v) if v.abs.is_zero() => 3,
+        Value::Relative(_) => 4,
+        Value::Datetime(Datetime::Date(_)) => 0,
+        Value::Datetime(Datetime::Time(_)) => 1,
+        Value::Datetime(Datetime::Datetime(_)) => 2,
+        _ => 0,
+    }
+}
+
+/// Implements casting of tuples from and into arrays of matching length.
+macro_rules! tuple_cast {
+    ($len:literal: $($t:ident),+) => {
+        impl<$($t),+> Reflect for ($($t,)+) {
+            fn input() -> CastInfo {
+                Array::input()
+            }
+
+            fn output() -> CastInfo {
+                Array::output()
+            }
+
+            fn castable(value: &Value) -> bool {
+                Array::castable(value)
+            }
+        }
+
+        impl<$($t: IntoValue),+> IntoValue for ($($t,)+) {
+            #[allow(non_snake_case)]
+            fn into_value(self) -> Value {
+                let ($($t,)+) = self;
+                Value::Array(array![$($t),+])
+            }
+        }
+
+        impl<$($t: FromValue),+> FromValue for ($($t,)+) {
+            fn from_value(value: Value) -> StrResult<Self> {
+                let array = value.cast::<Array>()?;
+                if array.len() != $len {
+                    bail!(
+                        "array must contain exactly {} entries (found {})",
+                        $len,
+                        array.len(),
+                    );
+                }
+
+                let mut iter = array.into_iter();
+                Ok(($(iter.next().unwrap().cast::<$t>()?,)+))
+            }
+        }
+    };
+}
+
+tuple_cast!(2: A, B);
+tuple_cast!(3: A, B, C);
+tuple_cast!(4: A, B, C, D);
+
 /// The error message when the array is empty.
 #[cold]
 fn array_is_empty() -> EcoString {
@@ -936,3 +2728,38 @@ fn out_of_bounds_no_default(index: i64, len: usize) -> EcoString {
          and no default value was specified",
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuple_cast() {
+        // Round trip.
+        let value = (1i64, Str::from("a"), 2.5).into_value();
+        assert_eq!(value, Value::Array(array![1, "a", 2.5]));
+        assert_eq!(value.cast::<(i64, Str, f64)>().unwrap(), (1, Str::from("a"), 2.5));
+
+        // Length mismatch.
+        assert_eq!(
+            array![1, 2, 3].into_value().cast::<(i64, i64)>().unwrap_err(),
+            "array must contain exactly 2 entries (found 3)",
+        );
+
+        // Element cast error.
+        assert_eq!(
+            array![1, "a"].into_value().cast::<(i64, i64)>().unwrap_err(),
+            "expected integer, found string",
+        );
+    }
+
+    #[test]
+    fn test_cast_all() {
+        assert_eq!(array![1, 2, 3].cast_all::<i64>().unwrap(), vec![1, 2, 3]);
+        assert_eq!(array![].cast_all::<i64>().unwrap(), Vec::<i64>::new());
+        assert_eq!(
+            array![1, 2, "c", "d"].cast_all::<i64>().unwrap_err(),
+            "expected integer, found string (at index 2)",
+        );
+    }
+}
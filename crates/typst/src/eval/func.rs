@@ -2,19 +2,19 @@ use std::fmt::Debug;
 use std::sync::Arc;
 
 use comemo::{Prehashed, Tracked, TrackedMut};
-use ecow::EcoString;
+use ecow::{eco_format, EcoString};
 use once_cell::sync::Lazy;
 
 use super::{
-    cast, scope, ty, Args, CastInfo, Eval, FlowEvent, IntoValue, Route, Scope, Scopes,
-    Tracer, Type, Value, Vm,
+    cast, dict, scope, ty, Arg, Args, Array, CastInfo, Dict, Eval, FlowEvent, IntoValue,
+    Route, Scope, Scopes, Str, Tracer, Type, Value, Vm, MAX_ITERATIONS,
 };
-use crate::diag::{bail, SourceResult, StrResult};
+use crate::diag::{bail, error, At, SourceResult, StrResult};
 use crate::model::{
     Content, DelayedErrors, Element, Introspector, Locator, Selector, Vt,
 };
 use crate::syntax::ast::{self, AstNode};
-use crate::syntax::{FileId, Span, SyntaxNode};
+use crate::syntax::{FileId, Span, Spanned, SyntaxNode};
 use crate::util::Static;
 use crate::World;
 
@@ -118,6 +118,10 @@ pub use typst_macros::func;
 /// The only exception are built-in methods like
 /// [`array.push(value)`]($array.push). These can modify the values they are
 /// called on.
+///
+/// Because of this, Typst caches the results of calls to custom functions.
+/// Calling a function a second time with the same arguments reuses the
+/// earlier result instead of evaluating the function body again.
 #[ty(scope, name = "function")]
 #[derive(Debug, Clone, Hash)]
 #[allow(clippy::derived_hash_with_manual_eq)]
@@ -139,30 +143,56 @@ enum Repr {
     Closure(Arc<Prehashed<Closure>>),
     /// A nested function with pre-applied arguments.
     With(Arc<(Func, Args)>),
+    /// A nested function with pre-applied arguments that are passed after the
+    /// arguments given at the call site.
+    WithLast(Arc<(Func, Args)>),
+    /// A composition of two functions, where the second one is called first.
+    Compose(Arc<(Func, Func)>),
+    /// A curried function and the number of positional arguments it still
+    /// needs before it is called.
+    Curry(Arc<(Func, usize)>),
+    /// A function that is applied the given number of times, each time to
+    /// the result of the previous call.
+    Times(Arc<(Func, usize)>),
+    /// A sequence of functions, where each one is called with the result of
+    /// the previous one. An empty pipeline returns its argument unchanged.
+    Pipeline(Arc<[Func]>),
+    /// A function that ignores its arguments and always returns the value.
+    Constant(Arc<Value>),
 }
 
 impl Func {
     /// The function's name (e.g. `min`).
     ///
-    /// Returns `None` if this is an anonymous closure.
+    /// Returns `None` if this is an anonymous closure or a composition.
     pub fn name(&self) -> Option<&str> {
         match &self.repr {
             Repr::Native(native) => Some(native.name),
             Repr::Element(elem) => Some(elem.name()),
             Repr::Closure(closure) => closure.name(),
-            Repr::With(with) => with.0.name(),
+            Repr::With(with) | Repr::WithLast(with) => with.0.name(),
+            Repr::Compose(_) => None,
+            Repr::Curry(curry) => curry.0.name(),
+            Repr::Times(_) => None,
+            Repr::Pipeline(_) => None,
+            Repr::Constant(_) => None,
         }
     }
 
     /// The function's title case name, for use in documentation (e.g. `Minimum`).
     ///
-    /// Returns `None` if this is a closure.
+    /// Returns `None` if this is a closure or a composition.
     pub fn title(&self) -> Option<&'static str> {
         match &self.repr {
             Repr::Native(native) => Some(native.title),
             Repr::Element(elem) => Some(elem.title()),
             Repr::Closure(_) => None,
-            Repr::With(with) => with.0.title(),
+            Repr::With(with) | Repr::WithLast(with) => with.0.title(),
+            Repr::Compose(_) => None,
+            Repr::Curry(curry) => curry.0.title(),
+            Repr::Times(_) => None,
+            Repr::Pipeline(_) => None,
+            Repr::Constant(_) => None,
         }
     }
 
@@ -172,7 +202,12 @@ impl Func {
             Repr::Native(native) => Some(native.docs),
             Repr::Element(elem) => Some(elem.docs()),
             Repr::Closure(_) => None,
-            Repr::With(with) => with.0.docs(),
+            Repr::With(with) | Repr::WithLast(with) => with.0.docs(),
+            Repr::Compose(_) => None,
+            Repr::Curry(curry) => curry.0.docs(),
+            Repr::Times(_) => None,
+            Repr::Pipeline(_) => None,
+            Repr::Constant(_) => None,
         }
     }
 
@@ -182,7 +217,12 @@ impl Func {
             Repr::Native(native) => Some(&native.0.params),
             Repr::Element(elem) => Some(elem.params()),
             Repr::Closure(_) => None,
-            Repr::With(with) => with.0.params(),
+            Repr::With(with) | Repr::WithLast(with) => with.0.params(),
+            Repr::Compose(compose) => compose.1.params(),
+            Repr::Curry(curry) => curry.0.params(),
+            Repr::Times(times) => times.0.params(),
+            Repr::Pipeline(funcs) => funcs.first()?.params(),
+            Repr::Constant(_) => None,
         }
     }
 
@@ -191,6 +231,97 @@ impl Func {
         self.params()?.iter().find(|param| param.name == name)
     }
 
+    /// The parameters of this function as they can be seen from a script.
+    ///
+    /// Unlike [`params`](Self::params), this also works for closures. For
+    /// functions with pre-applied arguments, positional parameters that were
+    /// already filled are removed and applied named arguments become the new
+    /// defaults.
+    pub fn signature(&self) -> Vec<FuncParam> {
+        match &self.repr {
+            Repr::Native(native) => native.0.params.iter().map(FuncParam::from).collect(),
+            Repr::Element(elem) => elem.params().iter().map(FuncParam::from).collect(),
+            Repr::Closure(closure) => closure.signature(),
+            Repr::With(with) | Repr::WithLast(with) => {
+                // Arguments applied with `with-last` fill the positional
+                // parameters from the back.
+                let last = matches!(self.repr, Repr::WithLast(_));
+                let mut params = with.0.signature();
+                let mut skip =
+                    with.1.items.iter().filter(|arg| arg.name.is_none()).count();
+                if last {
+                    params.reverse();
+                }
+                params.retain(|param| {
+                    if skip > 0 && param.positional && !param.variadic {
+                        skip -= 1;
+                        return false;
+                    }
+                    true
+                });
+                if last {
+                    params.reverse();
+                }
+                for arg in &with.1.items {
+                    let Some(name) = &arg.name else { continue };
+                    if let Some(param) = params
+                        .iter_mut()
+                        .find(|param| param.named && param.name.as_str() == name.as_str())
+                    {
+                        param.default = Some(arg.value.v.clone());
+                        param.required = false;
+                    }
+                }
+                params
+            }
+            Repr::Compose(compose) => compose.1.signature(),
+            Repr::Curry(curry) => curry.0.signature(),
+            Repr::Times(times) => times.0.signature(),
+            Repr::Pipeline(funcs) => match funcs.first() {
+                Some(first) => first.signature(),
+                None => vec![FuncParam {
+                    name: "value".into(),
+                    default: None,
+                    positional: true,
+                    named: false,
+                    variadic: false,
+                    required: true,
+                }],
+            },
+            Repr::Constant(_) => vec![FuncParam {
+                name: EcoString::new(),
+                default: None,
+                positional: true,
+                named: false,
+                variadic: true,
+                required: false,
+            }],
+        }
+    }
+
+    /// A human-readable signature of a closure, like `f(a, b: 1, ..rest)`.
+    ///
+    /// Returns `None` if this is not a closure.
+    pub fn debug_signature(&self) -> Option<EcoString> {
+        let Repr::Closure(closure) = &self.repr else { return None };
+        let mut buf = EcoString::from(closure.name().unwrap_or_default());
+        buf.push('(');
+        for (i, param) in self.signature().into_iter().enumerate() {
+            if i > 0 {
+                buf.push_str(", ");
+            }
+            if param.variadic {
+                buf.push_str("..");
+            }
+            buf.push_str(&param.name);
+            if let Some(default) = &param.default {
+                buf.push_str(&eco_format!(": {}", super::Repr::repr(default)));
+            }
+        }
+        buf.push(')');
+        Some(buf)
+    }
+
     /// Get details about the function's return type.
     pub fn returns(&self) -> Option<&'static CastInfo> {
         static CONTENT: Lazy<CastInfo> =
@@ -199,7 +330,12 @@ impl Func {
             Repr::Native(native) => Some(&native.0.returns),
             Repr::Element(_) => Some(&CONTENT),
             Repr::Closure(_) => None,
-            Repr::With(with) => with.0.returns(),
+            Repr::With(with) | Repr::WithLast(with) => with.0.returns(),
+            Repr::Compose(compose) => compose.0.returns(),
+            Repr::Curry(curry) => curry.0.returns(),
+            Repr::Times(times) => times.0.returns(),
+            Repr::Pipeline(funcs) => funcs.last()?.returns(),
+            Repr::Constant(_) => None,
         }
     }
 
@@ -209,8 +345,28 @@ impl Func {
             Repr::Native(native) => native.keywords,
             Repr::Element(elem) => elem.keywords(),
             Repr::Closure(_) => &[],
-            Repr::With(with) => with.0.keywords(),
+            Repr::With(with) | Repr::WithLast(with) => with.0.keywords(),
+            Repr::Compose(_) => &[],
+            Repr::Curry(curry) => curry.0.keywords(),
+            Repr::Times(_) => &[],
+            Repr::Pipeline(_) => &[],
+            Repr::Constant(_) => &[],
+        }
+    }
+
+    /// Counts the function's parameters by kind.
+    pub fn arity(&self) -> Arity {
+        let mut arity = Arity::default();
+        for param in self.signature() {
+            if param.variadic {
+                arity.variadic = true;
+            } else if param.required {
+                arity.required += 1;
+            } else {
+                arity.optional += 1;
+            }
         }
+        arity
     }
 
     /// The function's associated scope of sub-definition.
@@ -219,7 +375,12 @@ impl Func {
             Repr::Native(native) => Some(&native.0.scope),
             Repr::Element(elem) => Some(elem.scope()),
             Repr::Closure(_) => None,
-            Repr::With(with) => with.0.scope(),
+            Repr::With(with) | Repr::WithLast(with) => with.0.scope(),
+            Repr::Compose(_) => None,
+            Repr::Curry(curry) => curry.0.scope(),
+            Repr::Times(_) => None,
+            Repr::Pipeline(_) => None,
+            Repr::Constant(_) => None,
         }
     }
 
@@ -236,6 +397,55 @@ impl Func {
         }
     }
 
+    /// The file in which the function was defined.
+    ///
+    /// Returns `None` for functions that are not user-defined and for
+    /// closures defined outside of any file.
+    pub fn location(&self) -> Option<FileId> {
+        match &self.repr {
+            Repr::Closure(closure) => closure.file,
+            Repr::With(with) | Repr::WithLast(with) => with.0.location(),
+            Repr::Curry(curry) => curry.0.location(),
+            _ => None,
+        }
+    }
+
+    /// Whether two functions are structurally equal, ignoring where they
+    /// were defined.
+    ///
+    /// Closures are equal if they have the same syntax, default values,
+    /// captured variables, and name. Other functions fall back to `==`.
+    pub fn spanless_eq(&self, other: &Self) -> bool {
+        match (&self.repr, &other.repr) {
+            (Repr::Closure(a), Repr::Closure(b)) => {
+                a.node.spanless_eq(&b.node)
+                    && a.defaults == b.defaults
+                    && a.display_name == b.display_name
+                    && a.captured.iter().eq(b.captured.iter())
+            }
+            (Repr::With(a), Repr::With(b)) | (Repr::WithLast(a), Repr::WithLast(b)) => {
+                a.0.spanless_eq(&b.0)
+                    && a.1.items.len() == b.1.items.len()
+                    && a.1
+                        .items
+                        .iter()
+                        .zip(b.1.items.iter())
+                        .all(|(x, y)| x.name == y.name && x.value.v == y.value.v)
+            }
+            (Repr::Compose(a), Repr::Compose(b)) => {
+                a.0.spanless_eq(&b.0) && a.1.spanless_eq(&b.1)
+            }
+            (Repr::Curry(a), Repr::Curry(b)) | (Repr::Times(a), Repr::Times(b)) => {
+                a.0.spanless_eq(&b.0) && a.1 == b.1
+            }
+            (Repr::Pipeline(a), Repr::Pipeline(b)) => {
+                a.len() == b.len()
+                    && a.iter().zip(b.iter()).all(|(x, y)| x.spanless_eq(y))
+            }
+            _ => self == other,
+        }
+    }
+
     /// Extract the element function, if it is one.
     pub fn element(&self) -> Option<Element> {
         match self.repr {
@@ -245,7 +455,62 @@ impl Func {
     }
 
     /// Call the function with the given arguments.
-    pub fn call_vm(&self, vm: &mut Vm, mut args: Args) -> SourceResult<Value> {
+    pub fn call_vm(&self, vm: &mut Vm, args: Args) -> SourceResult<Value> {
+        self.call_impl(vm, args, true, None, vm.detached)
+    }
+
+    /// Call the function as a callback of the method with the given name
+    /// (e.g. `map`).
+    ///
+    /// If a `break` or `continue` escapes the function, the resulting error
+    /// names the method.
+    pub fn call_in(
+        &self,
+        vm: &mut Vm,
+        args: Args,
+        method: &'static str,
+    ) -> SourceResult<Value> {
+        self.call_impl(vm, args, true, Some(method), vm.detached)
+    }
+
+    /// Call the function with the given arguments, silently dropping any
+    /// arguments that the function doesn't accept instead of failing with an
+    /// "unexpected argument" error.
+    pub fn call_lenient(&self, vm: &mut Vm, args: Args) -> SourceResult<Value> {
+        self.call_impl(vm, args, false, None, vm.detached)
+    }
+
+    /// Whether the function can be called with `argc` positional arguments.
+    ///
+    /// This allows to validate callbacks up front, so that a friendly error
+    /// can be reported before calling them.
+    pub fn arity_matches(&self, argc: usize) -> bool {
+        let (mut min, mut max, mut variadic) = (0, 0, false);
+        for param in self.signature() {
+            if param.variadic {
+                variadic = true;
+            } else if param.positional {
+                max += 1;
+                if param.required {
+                    min += 1;
+                }
+            }
+        }
+        argc >= min && (variadic || argc <= max)
+    }
+
+    /// Call the function, ensuring that all arguments were used if `strict`
+    /// is true. The `method` is the name of the method that the function is a
+    /// callback of, if any. The function is `detached` if Typst itself called
+    /// it or one of its callers.
+    fn call_impl(
+        &self,
+        vm: &mut Vm,
+        mut args: Args,
+        strict: bool,
+        method: Option<&'static str>,
+        detached: bool,
+    ) -> SourceResult<Value> {
         let _span = tracing::info_span!(
             "call",
             name = self.name().unwrap_or("<anon>"),
@@ -255,12 +520,16 @@ impl Func {
         match &self.repr {
             Repr::Native(native) => {
                 let value = (native.function)(vm, &mut args)?;
-                args.finish()?;
+                if strict {
+                    args.finish()?;
+                }
                 Ok(value)
             }
             Repr::Element(func) => {
                 let value = func.construct(vm, &mut args)?;
-                args.finish()?;
+                if strict {
+                    args.finish()?;
+                }
                 Ok(Value::Content(value))
             }
             Repr::Closure(closure) => {
@@ -277,13 +546,88 @@ impl Func {
                     TrackedMut::reborrow_mut(&mut vm.vt.delayed),
                     TrackedMut::reborrow_mut(&mut vm.vt.tracer),
                     vm.depth + 1,
+                    strict,
+                    method,
+                    detached,
                     args,
                 )
             }
             Repr::With(with) => {
                 args.items = with.1.items.iter().cloned().chain(args.items).collect();
-                with.0.call_vm(vm, args)
+                with.0.call_impl(vm, args, strict, method, detached)
+            }
+            Repr::WithLast(with) => {
+                args.items.extend(with.1.items.iter().cloned());
+                with.0.call_impl(vm, args, strict, method, detached)
+            }
+            Repr::Compose(compose) => {
+                let span = args.span;
+                let value = compose.1.call_impl(vm, args, strict, method, detached)?;
+                compose.0.call_impl(
+                    vm,
+                    Args::new(span, [value]),
+                    strict,
+                    method,
+                    detached,
+                )
             }
+            Repr::Curry(curry) => {
+                let (func, remaining) = &**curry;
+                let given = args.items.iter().filter(|arg| arg.name.is_none()).count();
+                if given >= *remaining {
+                    return func.call_impl(vm, args, strict, method, detached);
+                }
+
+                let func = func.clone().with(args);
+                let repr = Repr::Curry(Arc::new((func, remaining - given)));
+                Ok(Value::Func(Self { repr, span: self.span }))
+            }
+            Repr::Times(times) => {
+                let (func, n) = &**times;
+                if *n == 0 {
+                    let value = args.expect::<Value>("value")?;
+                    if strict {
+                        args.finish()?;
+                    }
+                    return Ok(value);
+                }
+
+                let span = args.span;
+                let mut value = func.call_impl(vm, args, strict, method, detached)?;
+                for _ in 1..*n {
+                    value = func.call_impl(
+                        vm,
+                        Args::new(span, [value]),
+                        strict,
+                        method,
+                        detached,
+                    )?;
+                }
+                Ok(value)
+            }
+            Repr::Pipeline(funcs) => {
+                let Some((first, rest)) = funcs.split_first() else {
+                    let value = args.expect::<Value>("value")?;
+                    if strict {
+                        args.finish()?;
+                    }
+                    return Ok(value);
+                };
+
+                let span = args.span;
+                let mut value = first.call_impl(vm, args, strict, method, detached)?;
+                for func in rest {
+                    value = func.call_impl(
+                        vm,
+                        Args::new(span, [value]),
+                        strict,
+                        method,
+                        detached,
+                    )?;
+                }
+                Ok(value)
+            }
+            Repr::Constant(value) => Ok(value.as_ref().clone()),
         }
     }
 
@@ -306,7 +650,7 @@ impl Func {
         };
         let mut vm = Vm::new(vt, route.track(), None, scopes);
         let args = Args::new(self.span(), args);
-        self.call_vm(&mut vm, args)
+        self.call_impl(&mut vm, args, true, None, true)
     }
 
     /// The function's span.
@@ -326,6 +670,15 @@ impl Func {
 #[scope]
 impl Func {
     /// Returns a new function that has the given arguments pre-applied.
+    ///
+    /// Named arguments replace earlier pre-applied arguments with the same
+    /// name, while positional arguments are appended to the existing ones.
+    ///
+    /// ```example
+    /// #let tag(body, fill: gray) = box(fill: fill, inset: 2pt, body)
+    /// #let red-tag = tag.with(fill: red)
+    /// #red-tag.with(fill: green)[Ok]
+    /// ```
     #[func]
     pub fn with(
         self,
@@ -338,9 +691,393 @@ impl Func {
         arguments: Vec<Args>,
     ) -> Func {
         let span = self.span;
+        if let Repr::With(with) = &self.repr {
+            let mut items = with.1.items.clone();
+            items.retain(|item| match &item.name {
+                Some(name) => {
+                    !args.items.iter().any(|arg| arg.name.as_ref() == Some(name))
+                }
+                None => true,
+            });
+            items.extend(args.items);
+            let args = Args { span: args.span, items };
+            return Self {
+                repr: Repr::With(Arc::new((with.0.clone(), args))),
+                span,
+            };
+        }
         Self { repr: Repr::With(Arc::new((self, args))), span }
     }
 
+    /// Returns a new function with the given value pre-applied as its first
+    /// open positional argument.
+    ///
+    /// This is the same as `{f.with(receiver)}`, but makes the intent clearer
+    /// when emulating methods with functions stored in dictionaries.
+    ///
+    /// ```example
+    /// #let counter = (count: 3)
+    /// #let describe(self, unit) = str(self.count) + " " + unit
+    /// #let method = describe.bind-self(counter)
+    /// #method("items")
+    /// ```
+    #[func]
+    pub fn bind_self(
+        self,
+        /// The callsite span.
+        span: Span,
+        /// The value to bind as the first argument.
+        receiver: Value,
+    ) -> Func {
+        self.with(Args::new(span, [receiver]))
+    }
+
+    /// Returns a new function that has the given arguments pre-applied after
+    /// the ones given at the call site.
+    ///
+    /// While [`with`]($function.with) fills the first positional parameters,
+    /// this fills the last ones. This is useful to pre-apply trailing
+    /// configuration while the data is passed as the first argument.
+    ///
+    /// ```example
+    /// #let clamp-to(x, low, high) = calc.max(low, calc.min(x, high))
+    /// #(-5, 3, 12).map(clamp-to.with-last(0, 10))
+    /// ```
+    #[func]
+    pub fn with_last(
+        self,
+        /// The real arguments (the other argument is just for the docs).
+        /// The docs argument cannot be called `args`.
+        args: Args,
+        /// The arguments to apply to the function.
+        #[external]
+        #[variadic]
+        arguments: Vec<Args>,
+    ) -> Func {
+        let span = self.span;
+        Self { repr: Repr::WithLast(Arc::new((self, args))), span }
+    }
+
+    /// Whether this function is structurally equal to another one.
+    ///
+    /// Unlike `==`, this considers two custom functions with the same
+    /// definition equal even if they were defined in different places.
+    ///
+    /// ```example
+    /// #let f = x => x + 1
+    /// #let g = x => x + 1
+    /// #(f == g) \
+    /// #f.structurally-eq(g)
+    /// ```
+    #[func]
+    pub fn structurally_eq(
+        &self,
+        /// The function to compare with.
+        other: Func,
+    ) -> bool {
+        self.spanless_eq(&other)
+    }
+
+    /// Returns a copy of a custom function with the given name.
+    ///
+    /// The name is used wherever the function is displayed, for example in
+    /// its representation. Renaming only works for custom functions. Other
+    /// functions are returned unchanged.
+    ///
+    /// ```example
+    /// #let f = x => x
+    /// #repr(f) \
+    /// #repr(f.rename("identity"))
+    /// ```
+    #[func]
+    pub fn rename(
+        self,
+        /// The new name.
+        name: Str,
+    ) -> Func {
+        let span = self.span;
+        match &self.repr {
+            Repr::Closure(closure) => {
+                let mut closure = (***closure).clone();
+                closure.display_name = Some(name.into());
+                Func::from(closure).spanned(span)
+            }
+            Repr::With(with) => {
+                let func = with.0.clone().rename(name);
+                let args = with.1.clone();
+                Self { repr: Repr::With(Arc::new((func, args))), span }
+            }
+            Repr::WithLast(with) => {
+                let func = with.0.clone().rename(name);
+                let args = with.1.clone();
+                Self { repr: Repr::WithLast(Arc::new((func, args))), span }
+            }
+            _ => self,
+        }
+    }
+
+    /// Returns a new function with a pre-applied named argument removed, so
+    /// that the parameter falls back to its default again.
+    ///
+    /// If no argument with the given name was pre-applied, the function is
+    /// returned unchanged.
+    ///
+    /// ```example
+    /// #let tag(body, fill: gray) = box(fill: fill, inset: 2pt, body)
+    /// #let red-tag = tag.with(fill: red)
+    /// #red-tag[A] #red-tag.without("fill")[B]
+    /// ```
+    #[func]
+    pub fn without(
+        self,
+        /// The name of the argument to remove.
+        name: Str,
+    ) -> Func {
+        let (Repr::With(with) | Repr::WithLast(with)) = &self.repr else {
+            return self;
+        };
+        if !with.1.items.iter().any(|arg| arg.name.as_ref() == Some(&name)) {
+            return self;
+        }
+
+        let mut args = with.1.clone();
+        args.items.retain(|arg| arg.name.as_ref() != Some(&name));
+        if args.items.is_empty() {
+            return with.0.clone();
+        }
+
+        let with = Arc::new((with.0.clone(), args));
+        let repr = match self.repr {
+            Repr::WithLast(_) => Repr::WithLast(with),
+            _ => Repr::With(with),
+        };
+        Self { repr, span: self.span }
+    }
+
+    /// Returns a new function that first calls the given function and then
+    /// passes its result to this one.
+    ///
+    /// All arguments to the resulting function are passed on to `other`.
+    ///
+    /// ```example
+    /// #let double(x) = 2 * x
+    /// #let inc(x) = x + 1
+    /// #double.compose(inc)(3)
+    /// ```
+    #[func]
+    pub fn compose(
+        self,
+        /// The function to call first.
+        other: Func,
+    ) -> Func {
+        let span = self.span;
+        Self { repr: Repr::Compose(Arc::new((self, other))), span }
+    }
+
+    /// Returns a new function that first calls this function and then passes
+    /// its result to the given one.
+    ///
+    /// If this function fails, the error is propagated and `next` is not
+    /// called. This is the same as `{next.compose(f)}`, but reads in the order
+    /// in which the functions are called.
+    ///
+    /// ```example
+    /// #let parse(s) = int(s)
+    /// #let double(x) = 2 * x
+    /// #parse.and-then(double)("21")
+    /// ```
+    #[func]
+    pub fn and_then(
+        self,
+        /// The function to call with the result.
+        next: Func,
+    ) -> Func {
+        next.compose(self)
+    }
+
+    /// Returns a new function that applies this function the given number of
+    /// times, passing each result on to the next call.
+    ///
+    /// All arguments to the resulting function are passed on to the first
+    /// call. If the count is zero, the resulting function returns its single
+    /// argument unchanged.
+    ///
+    /// ```example
+    /// #let double(x) = 2 * x
+    /// #double.times(3)(1) \
+    /// #range(4).map(n => double.times(n)(1))
+    /// ```
+    #[func]
+    pub fn times(
+        self,
+        /// How often to apply the function.
+        count: usize,
+    ) -> Func {
+        let span = self.span;
+        Self { repr: Repr::Times(Arc::new((self, count))), span }
+    }
+
+    /// Combines the given functions into a pipeline that calls them one after
+    /// another, from first to last.
+    ///
+    /// All arguments to the resulting function are passed on to the first
+    /// function. Each following function is called with the result of the
+    /// previous one. Without any functions, the resulting function returns
+    /// its single argument unchanged.
+    ///
+    /// ```example
+    /// #let steps = (s => s.trim(), upper, s => s + "!")
+    /// #function.compose-all(..steps)("  hello ")
+    /// ```
+    #[func]
+    pub fn compose_all(
+        /// The functions to combine.
+        #[variadic]
+        funcs: Vec<Func>,
+    ) -> Func {
+        let span = funcs.first().map_or(Span::detached(), |func| func.span);
+        Self { repr: Repr::Pipeline(funcs.into()), span }
+    }
+
+    /// Returns its argument unchanged.
+    ///
+    /// This is useful as a no-op in places that expect a function, for
+    /// example as a step in a [`compose-all`]($function.compose-all) pipeline.
+    ///
+    /// ```example
+    /// #let f = if false { upper } else { function.identity }
+    /// #f("unchanged")
+    /// ```
+    #[func]
+    pub fn identity(
+        /// The value to return.
+        value: Value,
+    ) -> Value {
+        value
+    }
+
+    /// Returns a function that ignores all arguments it is called with and
+    /// always returns the given value.
+    ///
+    /// ```example
+    /// #range(3).map(function.constant("x"))
+    /// ```
+    #[func]
+    pub fn constant(
+        /// The callsite span.
+        span: Span,
+        /// The value to return.
+        value: Value,
+    ) -> Func {
+        Self { repr: Repr::Constant(Arc::new(value)), span }
+    }
+
+    /// Returns a curried version of this function, which takes its required
+    /// positional arguments one at a time.
+    ///
+    /// Each call with fewer arguments than still needed returns another
+    /// curried function. Once all required positional arguments are given,
+    /// the original function is called. Named arguments are passed through.
+    /// Fails with an error for functions that take a variable number of
+    /// arguments.
+    ///
+    /// ```example
+    /// #let add3(a, b, c) = a + b + c
+    /// #let f = add3.curry()
+    /// #f(1)(2)(3) \
+    /// #(1, 2, 3).map(f(10)(20))
+    /// ```
+    #[func]
+    pub fn curry(self) -> StrResult<Func> {
+        if self.arity().variadic {
+            bail!("cannot curry a function with a variable number of arguments");
+        }
+
+        let remaining = self
+            .signature()
+            .iter()
+            .filter(|param| param.positional && param.required)
+            .count();
+        if remaining <= 1 {
+            return Ok(self);
+        }
+
+        let span = self.span;
+        Ok(Self {
+            repr: Repr::Curry(Arc::new((self, remaining))),
+            span,
+        })
+    }
+
+    /// Calls the function and returns its result or error as a dictionary,
+    /// instead of failing the compilation when the call fails.
+    ///
+    /// On success, the dictionary is `{(ok: true, value: ..)}`. On failure, it
+    /// is `{(ok: false, error: ..)}` with the message of the first error.
+    /// Stray control flow like a `break` outside of a loop and exceeding the
+    /// maximum call depth still fail the compilation.
+    ///
+    /// ```example
+    /// #let parse(s) = int(s)
+    /// #parse.try-call("12") \
+    /// #parse.try-call("twelve")
+    /// ```
+    #[func]
+    pub fn try_call(
+        self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The real arguments (the other argument is just for the docs).
+        /// The docs argument cannot be called `args`.
+        args: Args,
+        /// The arguments to call the function with.
+        #[external]
+        #[variadic]
+        arguments: Vec<Args>,
+    ) -> SourceResult<Dict> {
+        Ok(match self.call_vm(vm, args) {
+            Ok(value) => dict! { "ok" => true, "value" => value },
+            Err(errors) if errors.iter().any(|error| error.uncatchable) => {
+                return Err(errors)
+            }
+            Err(errors) => dict! {
+                "ok" => false,
+                "error" => errors.first().map(|error| error.message.clone()),
+            },
+        })
+    }
+
+    /// Calls the function with the pairs of the given dictionary as named
+    /// arguments.
+    ///
+    /// This is equivalent to `{f(..dict)}` and the named counterpart to an
+    /// array's [`apply`]($array.apply).
+    ///
+    /// ```example
+    /// #let settings = (fill: blue, size: 14pt)
+    /// #text.call-with-named(settings)[Hello]
+    /// ```
+    #[func]
+    pub fn call_with_named(
+        self,
+        /// The virtual machine.
+        vm: &mut Vm,
+        /// The callsite span.
+        span: Span,
+        /// The named arguments to call the function with.
+        named: Dict,
+    ) -> SourceResult<Value> {
+        let items = named
+            .into_iter()
+            .map(|(key, value)| Arg {
+                span,
+                name: Some(key),
+                value: Spanned::new(value, span),
+            })
+            .collect();
+        self.call_vm(vm, Args { span, items })
+    }
+
     /// Returns a selector that filters for elements belonging to this function
     /// whose fields have the values of the given arguments.
     #[func]
@@ -362,6 +1099,110 @@ impl Func {
             .ok_or("`where()` can only be called on element functions")?
             .where_(fields))
     }
+
+    /// Whether the function has a parameter with the given name that can be
+    /// passed as a named argument.
+    ///
+    /// Argument sinks are not taken into account, so this is `{false}` for a
+    /// name that is only accepted through a sink.
+    ///
+    /// ```example
+    /// #let f(body, fill: red) = none
+    /// #f.has-param("fill") \
+    /// #f.has-param("stroke")
+    /// ```
+    #[func]
+    pub fn has_param(
+        &self,
+        /// The name of the parameter.
+        name: Str,
+    ) -> bool {
+        self.signature()
+            .iter()
+            .any(|param| param.named && param.name.as_str() == name.as_str())
+    }
+
+    /// Returns the default value of the parameter with the given name.
+    ///
+    /// Returns `{none}` if the function has no such parameter or if the
+    /// parameter has no default. For functions with pre-applied arguments,
+    /// the applied value counts as the default.
+    ///
+    /// Defaults that refer to earlier parameters, like `height` in
+    /// `{(width, height: width) => width * height}`, are only computed when
+    /// the function is called, so they also yield `{none}`. To tell them apart from a
+    /// default that is `{none}` itself, check whether the parameter's entry
+    /// in [`params`]($function.params) has a `default` key.
+    ///
+    /// ```example
+    /// #let badge(body, size: 8pt) = none
+    /// #badge.default-of("size") \
+    /// #badge.with(size: 12pt).default-of("size")
+    /// ```
+    #[func]
+    pub fn default_of(
+        &self,
+        /// The name of the parameter.
+        name: Str,
+    ) -> Option<Value> {
+        self.signature()
+            .into_iter()
+            .find(|param| param.name.as_str() == name.as_str())
+            .and_then(|param| param.default)
+    }
+
+    /// Returns how many parameters the function has.
+    ///
+    /// The result is a dictionary with the number of `required` parameters,
+    /// the number of `optional` parameters with a default value, and whether
+    /// the function takes any number of extra arguments (`variadic`).
+    ///
+    /// ```example
+    /// #let f(a, b, c: 3, ..rest) = none
+    /// #f.arity() \
+    /// #f.with(1).arity()
+    /// ```
+    #[func(name = "arity")]
+    pub fn arity_dict(&self) -> Dict {
+        let arity = self.arity();
+        dict! {
+            "required" => arity.required,
+            "optional" => arity.optional,
+            "variadic" => arity.variadic,
+        }
+    }
+
+    /// Whether the function has an argument sink that collects any number of
+    /// extra arguments.
+    ///
+    /// ```example
+    /// #let f(..args) = none
+    /// #f.is-variadic() \
+    /// #calc.abs.is-variadic()
+    /// ```
+    #[func]
+    pub fn is_variadic(&self) -> bool {
+        self.signature().iter().any(|param| param.variadic)
+    }
+
+    /// Returns the function's parameters as an array of dictionaries.
+    ///
+    /// Each dictionary has a `name` and the boolean flags `positional`,
+    /// `named`, `variadic`, and `required`. If the parameter has a default
+    /// value, it is available under the `default` key.
+    ///
+    /// ```example
+    /// #let greet(name, punct: "!") = [Hi #name#punct]
+    /// #greet.params().map(p => p.name)
+    /// ```
+    #[func(name = "params", title = "Parameters")]
+    pub fn param_list(&self) -> Array {
+        self.signature()
+            .into_iter()
+            .map(FuncParam::into_dict)
+            .map(Value::Dict)
+            .collect()
+    }
 }
 
 impl super::Repr for Func {
@@ -462,22 +1303,88 @@ pub struct ParamInfo {
     pub settable: bool,
 }
 
+/// Describes a function parameter as it can be seen from a script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuncParam {
+    /// The parameter's name. Empty for an unnamed argument sink.
+    pub name: EcoString,
+    /// The parameter's default value, if it has one.
+    pub default: Option<Value>,
+    /// Is the parameter positional?
+    pub positional: bool,
+    /// Is the parameter named?
+    pub named: bool,
+    /// Is the parameter an argument sink?
+    pub variadic: bool,
+    /// Is the parameter required?
+    pub required: bool,
+}
+
+impl FuncParam {
+    /// Convert the parameter into a dictionary for use in scripts.
+    pub fn into_dict(self) -> Dict {
+        let mut dict = dict! {
+            "name" => self.name,
+            "positional" => self.positional,
+            "named" => self.named,
+            "variadic" => self.variadic,
+            "required" => self.required,
+        };
+        if let Some(default) = self.default {
+            dict.insert("default".into(), default);
+        }
+        dict
+    }
+}
+
+/// The number of parameters of a function, by kind.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Arity {
+    /// The number of parameters that must be given.
+    pub required: usize,
+    /// The number of parameters that have a default value.
+    pub optional: usize,
+    /// Whether the function has an argument sink.
+    pub variadic: bool,
+}
+
+impl From<&ParamInfo> for FuncParam {
+    fn from(info: &ParamInfo) -> Self {
+        Self {
+            name: info.name.into(),
+            default: info.default.map(|default| default()),
+            positional: info.positional,
+            named: info.named,
+            variadic: info.variadic,
+            required: info.required,
+        }
+    }
+}
+
 /// A user-defined closure.
-#[derive(Debug, Hash)]
+#[derive(Debug, Clone, Hash)]
 pub(super) struct Closure {
     /// The closure's syntax node. Must be castable to `ast::Closure`.
     pub node: SyntaxNode,
     /// The source file where the closure was defined.
     pub file: Option<FileId>,
-    /// Default values of named parameters.
-    pub defaults: Vec<Value>,
+    /// Default values of named parameters. `None` for defaults that refer to
+    /// earlier parameters and are thus evaluated when the closure is called.
+    pub defaults: Vec<Option<Value>>,
     /// Captured values from outer scopes.
     pub captured: Scope,
+    /// A name that overrides the one from the syntax node when displaying the
+    /// closure. Recursive calls still go through the syntactic name.
+    pub display_name: Option<EcoString>,
 }
 
 impl Closure {
     /// The name of the closure.
     pub fn name(&self) -> Option<&str> {
+        if let Some(name) = &self.display_name {
+            return Some(name);
+        }
+
         self.node
             .cast::<ast::Closure>()
             .unwrap()
@@ -485,6 +1392,45 @@ impl Closure {
             .map(|ident| ident.as_str())
     }
 
+    /// The closure's parameters.
+    fn signature(&self) -> Vec<FuncParam> {
+        let closure = self.node.cast::<ast::Closure>().unwrap();
+        let mut defaults = self.defaults.iter();
+        closure
+            .params()
+            .children()
+            .map(|param| match param {
+                ast::Param::Pos(pattern) => FuncParam {
+                    name: pattern.to_untyped().clone().into_text(),
+                    default: None,
+                    positional: true,
+                    named: matches!(pattern, ast::Pattern::Normal(ast::Expr::Ident(_))),
+                    variadic: false,
+                    required: true,
+                },
+                ast::Param::Named(named) => FuncParam {
+                    name: named.name().get().clone(),
+                    default: defaults.next().cloned().flatten(),
+                    positional: false,
+                    named: true,
+                    variadic: false,
+                    required: false,
+                },
+                ast::Param::Sink(spread) => FuncParam {
+                    name: spread
+                        .name()
+                        .map(|ident| ident.get().clone())
+                        .unwrap_or_default(),
+                    default: None,
+                    positional: true,
+                    named: false,
+                    variadic: true,
+                    required: false,
+                },
+            })
+            .collect()
+    }
+
     /// Call the function in the context with the arguments.
     #[comemo::memoize]
     #[tracing::instrument(skip_all)]
@@ -498,6 +1444,9 @@ impl Closure {
         delayed: TrackedMut<DelayedErrors>,
         tracer: TrackedMut<Tracer>,
         depth: usize,
+        strict: bool,
+        method: Option<&'static str>,
+        detached: bool,
         mut args: Args,
     ) -> SourceResult<Value> {
         let Repr::Closure(this) = &func.repr else {
@@ -523,34 +1472,123 @@ impl Closure {
         // Prepare VM.
         let mut vm = Vm::new(vt, route, this.file, scopes);
         vm.depth = depth;
+        vm.detached = detached;
+
+        // Self-recursive calls in tail position don't nest. Instead, they
+        // start the closure over with the new arguments.
+        let mut strict = strict;
+        let mut iterations = 0;
+        loop {
+            // Provide the closure itself for recursive calls. This uses the
+            // syntactic name, which stays the same if the closure is renamed.
+            if let Some(name) = closure.name() {
+                vm.define(name, Value::Func(func.clone()));
+            }
+
+            this.bind(&mut vm, closure, &mut args, strict)?;
+
+            let output = match eval_tail(&mut vm, func, closure.body())? {
+                Tail::Value(value) => value,
+                Tail::Call(next) if vm.flow.is_none() => {
+                    iterations += 1;
+                    if iterations >= MAX_ITERATIONS {
+                        bail!(error!(next.span, "maximum function call depth exceeded")
+                            .uncatchable());
+                    }
+
+                    let mut scopes = Scopes::new(None);
+                    scopes.top = this.captured.clone();
+                    vm.scopes = scopes;
+                    args = next;
+                    strict = true;
+                    continue;
+                }
+                Tail::Call(next) => func.call_vm(&mut vm, next)?,
+            };
+
+            // Handle control flow.
+            match vm.flow {
+                Some(FlowEvent::Return(_, Some(explicit))) => return Ok(explicit),
+                Some(FlowEvent::Return(_, None)) => {}
+                Some(flow) => match method {
+                    Some(method) => bail!(flow.forbidden_in(method, detached)),
+                    None => bail!(flow.forbidden(detached)),
+                },
+                None => {}
+            }
 
-        // Provide the closure itself for recursive calls.
-        if let Some(name) = closure.name() {
-            vm.define(name, Value::Func(func.clone()));
+            return Ok(output);
         }
+    }
 
-        // Parse the arguments according to the parameter list.
+    /// Bind the arguments to the closure's parameters.
+    fn bind(
+        &self,
+        vm: &mut Vm,
+        closure: ast::Closure,
+        args: &mut Args,
+        strict: bool,
+    ) -> SourceResult<()> {
+        // Parse the arguments according to the parameter list. Positional
+        // parameters that are given by name don't take a positional argument.
+        let given_by_name = |ident: &ast::Ident| {
+            args.items
+                .iter()
+                .any(|arg| arg.name.as_deref() == Some(ident.as_str()))
+        };
         let num_pos_params = closure
             .params()
             .children()
-            .filter(|p| matches!(p, ast::Param::Pos(_)))
+            .filter(|p| match p {
+                ast::Param::Pos(ast::Pattern::Normal(ast::Expr::Ident(ident))) => {
+                    !given_by_name(ident)
+                }
+                ast::Param::Pos(_) => true,
+                _ => false,
+            })
             .count();
         let num_pos_args = args.to_pos().len();
         let sink_size = num_pos_args.checked_sub(num_pos_params);
 
+        // A positional argument that would shift past a parameter given by
+        // name was actually meant for that parameter.
+        let check_shift = |args: &Args, by_name: Option<Span>| match by_name {
+            Some(span) if args.items.iter().any(|arg| arg.name.is_none()) => {
+                bail!(span, "parameter given both positionally and by name")
+            }
+            _ => Ok(()),
+        };
+
         let mut sink = None;
         let mut sink_pos_values = None;
-        let mut defaults = this.defaults.iter();
+        let mut by_name = None;
+        let mut defaults = self.defaults.iter();
         for p in closure.params().children() {
             match p {
                 ast::Param::Pos(pattern) => match pattern {
                     ast::Pattern::Normal(ast::Expr::Ident(ident)) => {
-                        vm.define(ident, args.expect::<Value>(&ident)?)
+                        let span = args
+                            .items
+                            .iter()
+                            .find(|arg| arg.name.as_deref() == Some(ident.as_str()))
+                            .map(|arg| arg.span);
+                        let value = match args.named::<Value>(&ident)? {
+                            Some(value) => {
+                                by_name = by_name.or(span);
+                                value
+                            }
+                            None => {
+                                check_shift(args, by_name)?;
+                                args.expect::<Value>(&ident)?
+                            }
+                        };
+                        vm.define(ident, value)
                     }
                     ast::Pattern::Normal(_) => unreachable!(),
                     pattern => {
+                        check_shift(args, by_name)?;
                         super::define_pattern(
-                            &mut vm,
+                            vm,
                             pattern,
                             args.expect::<Value>("pattern parameter")?,
                         )?;
@@ -558,6 +1596,7 @@ impl Closure {
                 },
                 ast::Param::Sink(ident) => {
                     sink = ident.name();
+                    by_name = None;
                     if let Some(sink_size) = sink_size {
                         sink_pos_values = Some(args.consume(sink_size)?);
                     }
@@ -565,8 +1604,11 @@ impl Closure {
                 ast::Param::Named(named) => {
                     let name = named.name();
                     let default = defaults.next().unwrap();
-                    let value =
-                        args.named::<Value>(&name)?.unwrap_or_else(|| default.clone());
+                    let value = match (args.named::<Value>(&name)?, default) {
+                        (Some(value), _) => value,
+                        (None, Some(default)) => default.clone(),
+                        (None, None) => named.expr().eval(vm)?,
+                    };
                     vm.define(name, value);
                 }
             }
@@ -581,21 +1623,79 @@ impl Closure {
         }
 
         // Ensure all arguments have been used.
-        args.finish()?;
-
-        // Handle control flow.
-        let output = closure.body().eval(&mut vm)?;
-        match vm.flow {
-            Some(FlowEvent::Return(_, Some(explicit))) => return Ok(explicit),
-            Some(FlowEvent::Return(_, None)) => {}
-            Some(flow) => bail!(flow.forbidden()),
-            None => {}
+        if strict {
+            args.finish()?;
         }
 
-        Ok(output)
+        Ok(())
     }
 }
 
+/// The result of evaluating an expression in tail position of a closure.
+enum Tail {
+    /// The expression was evaluated to a value.
+    Value(Value),
+    /// The expression is a call of the closure itself with these arguments.
+    Call(Args),
+}
+
+/// Evaluate an expression in tail position of the closure `func`.
+///
+/// Direct calls of the closure itself are not performed, but returned as
+/// [`Tail::Call`]. Tail positions are the expression itself, the branches of a
+/// conditional, and the only expression of a code block.
+fn eval_tail(vm: &mut Vm, func: &Func, expr: ast::Expr) -> SourceResult<Tail> {
+    let tail = match expr {
+        ast::Expr::FuncCall(call) => {
+            if let ast::Expr::Ident(ident) = call.callee() {
+                if matches!(vm.scopes.get(&ident), Ok(Value::Func(f)) if f == func) {
+                    return Ok(Tail::Call(call.args().eval(vm)?));
+                }
+            }
+            return expr.eval(vm).map(Tail::Value);
+        }
+        ast::Expr::Conditional(conditional) => {
+            let condition = conditional.condition();
+            if condition.eval(vm)?.cast::<bool>().at(condition.span())? {
+                eval_tail(vm, func, conditional.if_body())?
+            } else if let Some(else_body) = conditional.else_body() {
+                eval_tail(vm, func, else_body)?
+            } else {
+                Tail::Value(Value::None)
+            }
+        }
+        ast::Expr::Code(block) => {
+            // Set and show rules must go through the regular code evaluation,
+            // which applies them to the rest of the block.
+            let mut exprs = block.body().exprs();
+            match (exprs.next(), exprs.next()) {
+                (Some(inner), None)
+                    if !matches!(inner, ast::Expr::Set(_) | ast::Expr::Show(_)) =>
+                {
+                    vm.scopes.enter();
+                    let tail = eval_tail(vm, func, inner)?;
+                    vm.scopes.exit();
+                    tail
+                }
+                _ => return expr.eval(vm).map(Tail::Value),
+            }
+        }
+        _ => return expr.eval(vm).map(Tail::Value),
+    };
+
+    // Finish the value like `Expr::eval` would have.
+    Ok(match tail {
+        Tail::Value(value) => {
+            let value = value.spanned(expr.span());
+            if vm.inspected == Some(expr.span()) {
+                vm.vt.tracer.value(value.clone());
+            }
+            Tail::Value(value)
+        }
+        call => call,
+    })
+}
+
 impl From<Closure> for Func {
     fn from(closure: Closure) -> Self {
         Repr::Closure(Arc::new(Prehashed::new(closure))).into()
@@ -657,20 +1757,10 @@ impl<'a> CapturesVisitor<'a> {
             }
 
             // A closure contains parameter bindings, which are bound before the
-            // body is evaluated. Care must be taken so that the default values
-            // of named parameters cannot access previous parameter bindings.
+            // body is evaluated. The default values of named parameters can
+            // access previous parameter bindings, but not the closure itself.
             Some(ast::Expr::Closure(expr)) => {
-                for param in expr.params().children() {
-                    if let ast::Param::Named(named) = param {
-                        self.visit(named.expr().to_untyped());
-                    }
-                }
-
                 self.internal.enter();
-                if let Some(name) = expr.name() {
-                    self.bind(name);
-                }
-
                 for param in expr.params().children() {
                     match param {
                         ast::Param::Pos(pattern) => {
@@ -678,13 +1768,20 @@ impl<'a> CapturesVisitor<'a> {
                                 self.bind(ident);
                             }
                         }
-                        ast::Param::Named(named) => self.bind(named.name()),
+                        ast::Param::Named(named) => {
+                            self.visit(named.expr().to_untyped());
+                            self.bind(named.name());
+                        }
                         ast::Param::Sink(spread) => {
                             self.bind(spread.name().unwrap_or_default())
                         }
                     }
                 }
 
+                if let Some(name) = expr.name() {
+                    self.bind(name);
+                }
+
                 self.visit(expr.body().to_untyped());
                 self.internal.exit();
             }
@@ -772,7 +1869,30 @@ impl<'a> CapturesVisitor<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::syntax::parse;
+    use crate::syntax::{parse, parse_code, SyntaxKind, VirtualPath};
+
+    #[func]
+    fn add(a: i64, #[default] b: i64) -> i64 {
+        a + b
+    }
+
+    /// Create a function from the first closure in the code.
+    fn closure(code: &str, defaults: Vec<Option<Value>>) -> Func {
+        fn find(node: &SyntaxNode) -> Option<SyntaxNode> {
+            if node.kind() == SyntaxKind::Closure {
+                return Some(node.clone());
+            }
+            node.children().find_map(find)
+        }
+
+        Func::from(Closure {
+            node: find(&parse_code(code)).unwrap(),
+            file: None,
+            defaults,
+            captured: Scope::new(),
+            display_name: None,
+        })
+    }
 
     #[track_caller]
     fn test(text: &str, result: &[&str]) {
@@ -806,7 +1926,8 @@ mod tests {
         test("#((x, y) => x + z)", &["z"]);
         test("#((x: y, z) => x + z)", &["y"]);
         test("#((..x) => x + y)", &["y"]);
-        test("#((x, y: x + z) => x + y)", &["x", "z"]);
+        test("#((x, y: x + z) => x + y)", &["z"]);
+        test("#((y: x, x) => x + y)", &["x"]);
         test("#{x => x; x}", &["x"]);
 
         // Show rule.
@@ -835,4 +1956,62 @@ mod tests {
         test("#{ (body, a) = (y, 1) }", &["y"]);
         test("#(x.at(y) = 5)", &["x", "y"])
     }
+
+    #[test]
+    fn test_arity_matches() {
+        let matching =
+            |func: &Func| (0..5).filter(|&n| func.arity_matches(n)).collect::<Vec<_>>();
+        let span = Span::detached();
+
+        // Named parameters don't take positional arguments.
+        let f = closure("let f(a, b, c: 1) = none", vec![Some(1i64.into_value())]);
+        assert_eq!(matching(&f), vec![2]);
+
+        // Argument sinks take any number of extra arguments.
+        let g = closure("let g(a, ..rest) = none", vec![]);
+        assert_eq!(matching(&g), vec![1, 2, 3, 4]);
+
+        // Positional parameters with a default are optional.
+        let h = add::func();
+        assert_eq!(matching(&h), vec![1, 2]);
+
+        // Pre-applied positional arguments are no longer needed.
+        assert_eq!(matching(&f.with(Args::new(span, [1i64]))), vec![1]);
+        assert_eq!(matching(&g.with(Args::new(span, [1i64]))), vec![0, 1, 2, 3, 4]);
+        assert_eq!(matching(&h.with(Args::new(span, [1i64]))), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_debug_signature() {
+        let f = closure(
+            "let f(a, (b, c), d: 2, e: a, ..rest) = none",
+            vec![Some(2i64.into_value()), None],
+        );
+        assert_eq!(f.debug_signature().unwrap(), "f(a, (b, c), d: 2, e, ..rest)");
+
+        let g = closure("(x, y: \"hi\") => x", vec![Some("hi".into_value())]);
+        assert_eq!(g.debug_signature().unwrap(), "(x, y: \"hi\")");
+        assert_eq!(
+            g.rename("greet".into()).debug_signature().unwrap(),
+            "greet(x, y: \"hi\")"
+        );
+
+        assert_eq!(add::func().debug_signature(), None);
+    }
+
+    #[test]
+    fn test_location() {
+        let id = FileId::new(None, VirtualPath::new("main.typ"));
+        let f = closure("x => x", vec![]);
+        assert_eq!(f.location(), None);
+
+        let Repr::Closure(inner) = &f.repr else { unreachable!() };
+        let mut inner = (***inner).clone();
+        inner.file = Some(id);
+        let f = Func::from(inner);
+        assert_eq!(f.location(), Some(id));
+        assert_eq!(f.with(Args::new(Span::detached(), [1i64])).location(), Some(id));
+
+        assert_eq!(add::func().location(), None);
+    }
 }
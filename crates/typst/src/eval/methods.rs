@@ -1,12 +1,24 @@
 //! Handles special built-in methods on values.
 
-use super::{Args, Array, Dict, Str, Type, Value};
-use crate::diag::{At, SourceResult};
+use super::{Args, Array, Dict, Func, IntoValue, Place, Str, Type, Value, Vm};
+use crate::diag::{bail, At, SourceResult};
 use crate::syntax::Span;
 
 /// Whether a specific method is mutating.
 pub fn is_mutating(method: &str) -> bool {
-    matches!(method, "push" | "pop" | "insert" | "remove")
+    matches!(
+        method,
+        "push"
+            | "pop"
+            | "extract"
+            | "insert"
+            | "insert-many"
+            | "map-in-place"
+            | "remove"
+            | "retain"
+            | "swap"
+            | "splice"
+    )
 }
 
 /// Whether a specific method is an accessor.
@@ -23,8 +35,14 @@ pub fn mutable_methods_on(ty: Type) -> &'static [(&'static str, bool)] {
             ("at", true),
             ("pop", false),
             ("push", true),
+            ("extract", true),
             ("insert", true),
+            ("insert-many", true),
+            ("map-in-place", true),
             ("remove", true),
+            ("retain", true),
+            ("swap", true),
+            ("splice", true),
         ]
     } else if ty == Type::of::<Dict>() {
         &[("at", true), ("insert", true), ("remove", true)]
@@ -33,13 +51,33 @@ pub fn mutable_methods_on(ty: Type) -> &'static [(&'static str, bool)] {
     }
 }
 
-/// Call a mutating method on a value.
-pub fn call_mut(
-    value: &mut Value,
+/// Call a mutating method on the value at a place.
+pub(super) fn call_mut(
+    vm: &mut Vm,
+    place: &Place,
     method: &str,
     mut args: Args,
     span: Span,
 ) -> SourceResult<Value> {
+    if matches!(place.access(vm)?, Value::Array(_)) {
+        match method {
+            "retain" => {
+                let mask = test_mask(vm, place, &mut args, span, "retain")?;
+                return Ok(access_array(vm, place, span)?.retain_by(mask).into_value());
+            }
+            "extract" => {
+                let mask = test_mask(vm, place, &mut args, span, "extract")?;
+                return Ok(access_array(vm, place, span)?.extract_by(mask).into_value());
+            }
+            "map-in-place" => {
+                map_in_place(vm, place, &mut args, span)?;
+                return Ok(Value::None);
+            }
+            _ => {}
+        }
+    }
+
+    let value = place.access(vm)?;
     let ty = value.ty();
     let missing = || Err(missing_method(ty, method)).at(span);
     let mut output = Value::None;
@@ -51,11 +89,23 @@ pub fn call_mut(
             "insert" => {
                 array.insert(args.expect("index")?, args.expect("value")?).at(span)?
             }
+            "insert-many" => array
+                .insert_many(args.expect("index")?, args.expect("values")?)
+                .at(span)?,
             "remove" => {
                 output = array
                     .remove(args.expect("index")?, args.named("default")?)
                     .at(span)?
             }
+            "swap" => {
+                array.swap(args.expect("first")?, args.expect("second")?).at(span)?
+            }
+            "splice" => {
+                let start = args.expect("start")?;
+                let end = args.eat()?.unwrap_or_default();
+                let replacement = args.eat()?.unwrap_or_default();
+                output = Value::Array(array.splice(start, end, replacement).at(span)?)
+            }
             _ => return missing(),
         },
 
@@ -75,6 +125,61 @@ pub fn call_mut(
     Ok(output)
 }
 
+/// Evaluate the predicate of `retain` or `extract` for each item of the array
+/// at the place.
+///
+/// The predicate runs on a snapshot, so it sees the array as it was before
+/// the call.
+fn test_mask(
+    vm: &mut Vm,
+    place: &Place,
+    args: &mut Args,
+    span: Span,
+    method: &'static str,
+) -> SourceResult<Vec<bool>> {
+    let test = args.expect::<Func>("test")?;
+    args.finish()?;
+    let snapshot = access_array(vm, place, span)?.clone();
+    snapshot.test_mask(vm, &test, method)
+}
+
+/// Transform each item of the array at the place with a mapper.
+///
+/// The mapper needs the virtual machine, so the array can't stay borrowed
+/// while it runs. Instead, the place is accessed again for each item. The
+/// array's storage is only copied if it is shared, e.g. with a closure that
+/// captured it. If the mapper fails, the items before the failing one keep
+/// their new values.
+fn map_in_place(
+    vm: &mut Vm,
+    place: &Place,
+    args: &mut Args,
+    span: Span,
+) -> SourceResult<()> {
+    let mapper = args.expect::<Func>("mapper")?;
+    args.finish()?;
+    let mut i = 0;
+    while let Some(item) = access_array(vm, place, span)?.as_slice().get(i).cloned() {
+        let args = Args::new(mapper.span(), [item]);
+        let output = mapper.call_in(vm, args, "map-in-place")?;
+        *access_array(vm, place, span)?.at_mut(i as i64).at(span)? = output;
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Access the array at a place.
+fn access_array<'a>(
+    vm: &'a mut Vm,
+    place: &Place,
+    span: Span,
+) -> SourceResult<&'a mut Array> {
+    match place.access(vm)? {
+        Value::Array(array) => Ok(array),
+        value => bail!(span, "expected array, found {}", value.ty()),
+    }
+}
+
 /// Call an accessor method on a value.
 pub fn call_access<'a>(
     value: &'a mut Value,
@@ -53,7 +53,7 @@ pub use self::dict::{dict, Dict};
 pub use self::duration::Duration;
 pub use self::fields::fields_on;
 pub use self::func::{
-    func, CapturesVisitor, Func, NativeFunc, NativeFuncData, ParamInfo,
+    func, Arity, CapturesVisitor, Func, FuncParam, NativeFunc, NativeFuncData, ParamInfo,
 };
 pub use self::library::{set_lang_items, LangItems, Library};
 pub use self::methods::mutable_methods_on;
@@ -143,7 +143,7 @@ pub fn eval(
 
     // Handle control flow.
     if let Some(flow) = vm.flow {
-        bail!(flow.forbidden());
+        bail!(flow.forbidden(vm.detached));
     }
 
     // Assemble the module.
@@ -214,7 +214,7 @@ pub fn eval_string(
 
     // Handle control flow.
     if let Some(flow) = vm.flow {
-        bail!(flow.forbidden());
+        bail!(flow.forbidden(vm.detached));
     }
 
     Ok(output)
@@ -252,6 +252,9 @@ pub struct Vm<'a> {
     depth: usize,
     /// A span that is currently under inspection.
     inspected: Option<Span>,
+    /// Whether Typst itself called the function that is being evaluated
+    /// (e.g. for a show rule), so that no loop in the user's code surrounds it.
+    detached: bool,
 }
 
 impl<'a> Vm<'a> {
@@ -273,6 +276,7 @@ impl<'a> Vm<'a> {
             scopes,
             depth: 0,
             inspected,
+            detached: false,
         }
     }
 
@@ -323,8 +327,11 @@ pub enum FlowEvent {
 
 impl FlowEvent {
     /// Return an error stating that this control flow is forbidden.
-    pub fn forbidden(&self) -> SourceDiagnostic {
-        match *self {
+    ///
+    /// The `detached` flag indicates that Typst itself called the surrounding
+    /// function, so that no loop in the user's code can surround it.
+    pub fn forbidden(&self, detached: bool) -> SourceDiagnostic {
+        let error = match *self {
             Self::Break(span) => {
                 error!(span, "cannot break outside of loop")
             }
@@ -334,7 +341,32 @@ impl FlowEvent {
             Self::Return(span, _) => {
                 error!(span, "cannot return outside of function")
             }
-        }
+        };
+        error.with_hints(self.detached_hint(detached)).uncatchable()
+    }
+
+    /// Return an error stating that this control flow is forbidden in a
+    /// callback of the method with the given name.
+    pub fn forbidden_in(&self, method: &str, detached: bool) -> SourceDiagnostic {
+        let error = match *self {
+            Self::Break(span) => {
+                error!(span, "cannot break out of a `{method}` callback")
+            }
+            Self::Continue(span) => {
+                error!(span, "cannot continue in a `{method}` callback")
+            }
+            Self::Return(..) => return self.forbidden(detached),
+        };
+        error.with_hints(self.detached_hint(detached)).uncatchable()
+    }
+
+    /// Explain why a stray `break` or `continue` fails in a detached function.
+    fn detached_hint(&self, detached: bool) -> Option<EcoString> {
+        (detached && !matches!(self, Self::Return(..))).then(|| {
+            "this function was called by Typst itself, \
+             outside of any loop in your code"
+                .into()
+        })
     }
 }
 
@@ -1138,7 +1170,7 @@ impl Eval for ast::FuncCall<'_> {
     fn eval(self, vm: &mut Vm) -> SourceResult<Self::Output> {
         let span = self.span();
         if vm.depth >= MAX_CALL_DEPTH {
-            bail!(span, "maximum function call depth exceeded");
+            bail!(error!(span, "maximum function call depth exceeded").uncatchable());
         }
 
         let callee = self.callee();
@@ -1155,17 +1187,16 @@ impl Eval for ast::FuncCall<'_> {
 
             let target = if methods::is_mutating(&field) {
                 let mut args = args.eval(vm)?;
-                let target = target.access(vm)?;
+                let place = Place::resolve(vm, target)?;
+                let target = place.access(vm)?;
 
                 // Only arrays and dictionaries have mutable methods.
                 if matches!(target, Value::Array(_) | Value::Dict(_)) {
                     args.span = span;
+                    let world = vm.world();
                     let point = || Tracepoint::Call(Some(field.get().clone()));
-                    return methods::call_mut(target, &field, args, span).trace(
-                        vm.world(),
-                        point,
-                        span,
-                    );
+                    return methods::call_mut(vm, &place, &field, args, span)
+                        .trace(world, point, span);
                 }
 
                 target.clone()
@@ -1332,11 +1363,28 @@ impl Eval for ast::Closure<'_> {
 
     #[tracing::instrument(name = "Closure::eval", skip_all)]
     fn eval(self, vm: &mut Vm) -> SourceResult<Self::Output> {
-        // Evaluate default values of named parameters.
+        // Evaluate default values of named parameters. Defaults that refer to
+        // earlier parameters can only be evaluated when the closure is called.
         let mut defaults = Vec::new();
+        let mut bound = Vec::new();
         for param in self.params().children() {
-            if let ast::Param::Named(named) = param {
-                defaults.push(named.expr().eval(vm)?);
+            match param {
+                ast::Param::Pos(pattern) => {
+                    bound.extend(
+                        pattern.idents().into_iter().map(|ident| ident.get().clone()),
+                    );
+                }
+                ast::Param::Named(named) => {
+                    let mut visitor = CapturesVisitor::new(None);
+                    visitor.visit(named.expr().to_untyped());
+                    let lazy =
+                        visitor.finish().iter().any(|(name, _)| bound.contains(name));
+                    defaults.push(if lazy { None } else { Some(named.expr().eval(vm)?) });
+                    bound.push(named.name().get().clone());
+                }
+                ast::Param::Sink(spread) => {
+                    bound.extend(spread.name().map(|ident| ident.get().clone()));
+                }
             }
         }
 
@@ -1353,6 +1401,7 @@ impl Eval for ast::Closure<'_> {
             file: vm.file,
             defaults,
             captured,
+            display_name: None,
         };
 
         Ok(Value::Func(Func::from(closure).spanned(self.params().span())))
@@ -1419,7 +1468,7 @@ where
     for p in destruct.bindings() {
         match p {
             ast::DestructuringKind::Normal(expr) => {
-                let Ok(v) = value.at(i as i64, None) else {
+                let Some(v) = value.as_slice().get(i).cloned() else {
                     bail!(expr.span(), "not enough elements to destructure");
                 };
                 f(vm, expr, v)?;
@@ -1993,51 +2042,97 @@ trait Access {
 
 impl Access for ast::Expr<'_> {
     fn access<'a>(self, vm: &'a mut Vm) -> SourceResult<&'a mut Value> {
-        match self {
-            Self::Ident(v) => v.access(vm),
-            Self::Parenthesized(v) => v.access(vm),
-            Self::FieldAccess(v) => v.access(vm),
-            Self::FuncCall(v) => v.access(vm),
-            _ => {
-                let _ = self.eval(vm)?;
-                bail!(self.span(), "cannot mutate a temporary value");
-            }
-        }
+        Place::resolve(vm, self)?.access(vm)
     }
 }
 
-impl Access for ast::Ident<'_> {
-    fn access<'a>(self, vm: &'a mut Vm) -> SourceResult<&'a mut Value> {
-        let span = self.span();
-        let value = vm.scopes.get_mut(&self).at(span)?;
-        if vm.inspected == Some(span) {
-            vm.vt.tracer.value(value.clone());
+/// The location of a mutable value, like `{a.at(f()).b}`.
+///
+/// Resolving a place evaluates the arguments of its accessor calls once.
+/// Afterwards, it can be accessed repeatedly without evaluating anything
+/// again, e.g. in between calls back into user code.
+enum Place<'a> {
+    /// A variable.
+    Var(ast::Ident<'a>),
+    /// A field of a dictionary.
+    Field(Box<Place<'a>>, ast::FieldAccess<'a>),
+    /// A call to an accessor method with already evaluated arguments.
+    Accessor(Box<Place<'a>>, ast::FuncCall<'a>, ast::Ident<'a>, Args),
+}
+
+impl<'a> Place<'a> {
+    /// Resolve the place an expression refers to.
+    fn resolve(vm: &mut Vm, expr: ast::Expr<'a>) -> SourceResult<Self> {
+        match expr {
+            ast::Expr::Ident(ident) => {
+                let span = ident.span();
+                let value = vm.scopes.get_mut(&ident).at(span)?;
+                if vm.inspected == Some(span) {
+                    vm.vt.tracer.value(value.clone());
+                }
+                return Ok(Self::Var(ident));
+            }
+            ast::Expr::Parenthesized(parenthesized) => {
+                return Self::resolve(vm, parenthesized.expr());
+            }
+            ast::Expr::FieldAccess(access) => {
+                let target = Self::resolve(vm, access.target())?;
+                return Ok(Self::Field(Box::new(target), access));
+            }
+            ast::Expr::FuncCall(call) => {
+                if let ast::Expr::FieldAccess(access) = call.callee() {
+                    let method = access.field();
+                    if methods::is_accessor(&method) {
+                        let args = call.args().eval(vm)?;
+                        let target = Self::resolve(vm, access.target())?;
+                        return Ok(Self::Accessor(Box::new(target), call, method, args));
+                    }
+                }
+            }
+            _ => {}
         }
-        Ok(value)
-    }
-}
 
-impl Access for ast::Parenthesized<'_> {
-    fn access<'a>(self, vm: &'a mut Vm) -> SourceResult<&'a mut Value> {
-        self.expr().access(vm)
+        let _ = expr.eval(vm)?;
+        bail!(expr.span(), "cannot mutate a temporary value");
     }
-}
 
-impl Access for ast::FieldAccess<'_> {
-    fn access<'a>(self, vm: &'a mut Vm) -> SourceResult<&'a mut Value> {
-        access_dict(vm, self)?.at_mut(self.field().get()).at(self.span())
+    /// Access the value at this place.
+    fn access<'b>(&self, vm: &'b mut Vm) -> SourceResult<&'b mut Value> {
+        match self {
+            Self::Var(ident) => vm.scopes.get_mut(ident).at(ident.span()),
+            Self::Field(target, access) => {
+                let span = access.target().span();
+                as_dict(target.access(vm)?, span)?
+                    .at_mut(access.field().get())
+                    .at(access.span())
+            }
+            Self::Accessor(target, call, method, args) => {
+                let span = call.span();
+                let world = vm.world();
+                let value = target.access(vm)?;
+                let result = methods::call_access(value, method, args.clone(), span);
+                let point = || Tracepoint::Call(Some(method.get().clone()));
+                result.trace(world, point, span)
+            }
+        }
     }
 }
 
+/// Access the dictionary that a field access' target refers to.
 fn access_dict<'a>(
     vm: &'a mut Vm,
     access: ast::FieldAccess,
 ) -> SourceResult<&'a mut Dict> {
-    match access.target().access(vm)? {
+    let span = access.target().span();
+    as_dict(access.target().access(vm)?, span)
+}
+
+/// Ensure that the value whose fields are mutated is a dictionary.
+fn as_dict(value: &mut Value, span: Span) -> SourceResult<&mut Dict> {
+    match value {
         Value::Dict(dict) => Ok(dict),
         value => {
             let ty = value.ty();
-            let span = access.target().span();
             if matches!(
                 value, // those types have their own field getters
                 Value::Symbol(_) | Value::Content(_) | Value::Module(_) | Value::Func(_)
@@ -2057,23 +2152,3 @@ fn access_dict<'a>(
         }
     }
 }
-
-impl Access for ast::FuncCall<'_> {
-    fn access<'a>(self, vm: &'a mut Vm) -> SourceResult<&'a mut Value> {
-        if let ast::Expr::FieldAccess(access) = self.callee() {
-            let method = access.field();
-            if methods::is_accessor(&method) {
-                let span = self.span();
-                let world = vm.world();
-                let args = self.args().eval(vm)?;
-                let value = access.target().access(vm)?;
-                let result = methods::call_access(value, &method, args, span);
-                let point = || Tracepoint::Call(Some(method.get().clone()));
-                return result.trace(world, point, span);
-            }
-        }
-
-        let _ = self.eval(vm)?;
-        bail!(self.span(), "cannot mutate a temporary value");
-    }
-}
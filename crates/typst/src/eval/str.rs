@@ -451,7 +451,10 @@ impl Str {
                 Replacement::Str(s) => output.push_str(s),
                 Replacement::Func(func) => {
                     let args = Args::new(func.span(), [dict.into_value()]);
-                    let piece = func.call_vm(vm, args)?.cast::<Str>().at(func.span())?;
+                    let piece = func
+                        .call_in(vm, args, "replace")?
+                        .cast::<Str>()
+                        .at(func.span())?;
                     output.push_str(&piece);
                 }
             }
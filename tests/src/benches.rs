@@ -9,6 +9,7 @@ use typst::World;
 use unscanny::Scanner;
 
 const TEXT: &str = include_str!("../typ/compiler/bench.typ");
+const MAP: &str = "#range(100000).map(x => x + 1).len()";
 const FONT: &[u8] = include_bytes!("../../assets/fonts/LinLibertine_R.ttf");
 
 main!(
@@ -17,6 +18,7 @@ main!(
     bench_parse,
     bench_edit,
     bench_eval,
+    bench_eval_map,
     bench_typeset,
     bench_compile,
     bench_render,
@@ -65,6 +67,16 @@ fn bench_eval(iai: &mut Iai) {
     });
 }
 
+fn bench_eval_map(iai: &mut Iai) {
+    let world = BenchWorld::with_text(MAP);
+    let route = typst::eval::Route::default();
+    let mut tracer = typst::eval::Tracer::new();
+    iai.run(|| {
+        typst::eval::eval(world.track(), route.track(), tracer.track_mut(), &world.source)
+            .unwrap()
+    });
+}
+
 fn bench_typeset(iai: &mut Iai) {
     let world = BenchWorld::new();
     let route = typst::eval::Route::default();
@@ -102,6 +114,10 @@ struct BenchWorld {
 
 impl BenchWorld {
     fn new() -> Self {
+        Self::with_text(TEXT)
+    }
+
+    fn with_text(text: &str) -> Self {
         let font = Font::new(FONT.into(), 0).unwrap();
         let book = FontBook::from_fonts([&font]);
 
@@ -109,7 +125,7 @@ impl BenchWorld {
             library: Prehashed::new(typst_library::build()),
             book: Prehashed::new(book),
             font,
-            source: Source::detached(TEXT),
+            source: Source::detached(text),
         }
     }
 
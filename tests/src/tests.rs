@@ -20,10 +20,11 @@ use tiny_skia as sk;
 use unscanny::Scanner;
 use walkdir::WalkDir;
 
-use typst::diag::{bail, FileError, FileResult, Severity, StrResult};
+use typst::diag::{bail, FileError, FileResult, Severity, SourceResult, StrResult};
 use typst::doc::{Document, Frame, FrameItem, Meta};
 use typst::eval::{
-    eco_format, func, Bytes, Datetime, Library, NoneValue, Repr, Tracer, Value,
+    eco_format, func, Args, Bytes, Datetime, Func, Library, NoneValue, Repr, Tracer,
+    Value, Vm,
 };
 use typst::font::{Font, FontBook};
 use typst::geom::{Abs, Color, Smart};
@@ -183,6 +184,11 @@ fn library() -> Library {
         NoneValue
     }
 
+    #[func]
+    fn call_lenient(vm: &mut Vm, args: Args, func: Func) -> SourceResult<Value> {
+        func.call_lenient(vm, args)
+    }
+
     let mut lib = typst_library::build();
 
     // Set page width to 120pt with 10pt margins, so that the inner page is
@@ -200,6 +206,7 @@ fn library() -> Library {
     lib.global.scope_mut().define_func::<test>();
     lib.global.scope_mut().define_func::<test_repr>();
     lib.global.scope_mut().define_func::<print>();
+    lib.global.scope_mut().define_func::<call_lenient>();
     lib.global
         .scope_mut()
         .define("conifer", Color::from_u8(0x9f, 0xEB, 0x52, 0xFF));
@@ -63,6 +63,17 @@
 #test((1, 2, 3).at(2, default: 5), 3)
 #test((1, 2, 3).at(3, default: 5), 5)
 
+---
+// Test access with multiple indices.
+#test((1, 2, 3, 4).at(range(1, 3)), (2, 3))
+#test((1, 2, 3, 4).at(range(0)), ())
+#test((1, 2, 3).at((0, -1)), (1, 3))
+#test((1, 2).at(range(1, 4), default: 0), (2, 0, 0))
+
+---
+// Error: 2-27 array index out of bounds (index: 3, len: 3) and no default value was specified
+#(1, 2, 3).at(range(2, 5))
+
 ---
 // Test remove with default value.
 
@@ -105,6 +116,30 @@
 #test((2,).last(), 2)
 #test((1, 2, 3).first(), 1)
 #test((1, 2, 3).last(), 3)
+#test(().first(default: none), none)
+#test(().last(default: 0), 0)
+#test((1, 2).first(default: 0), 1)
+#test((1, 2).last(default: 0), 2)
+
+---
+// Test the `split-first` and `split-last` methods.
+#test((1,).split-first(), (1, ()))
+#test((1,).split-last(), ((), 1))
+#test((1, 2, 3).split-first(), (1, (2, 3)))
+#test((1, 2, 3).split-last(), ((1, 2), 3))
+#let sum(values) = if values == () { 0 } else {
+  let (head, tail) = values.split-first()
+  head + sum(tail)
+}
+#test(sum((1, 2, 3, 4)), 10)
+
+---
+// Error: 2-18 array is empty
+#().split-first()
+
+---
+// Error: 2-17 array is empty
+#().split-last()
 
 ---
 // Error: 2-12 array is empty
@@ -130,8 +165,76 @@
   let array = (0, 1, 2, 4, 5)
   array.insert(3, 3)
   test(array, range(6))
-  array.remove(1)
+  test(array.remove(1), 1)
   test(array, (0, 2, 3, 4, 5))
+  test(array.remove(-1), 5)
+  test(array.pop(), 4)
+  test(array, (0, 2, 3))
+}
+
+---
+// Test the `insert-many` method.
+#{
+  let array = (1, 2, 5)
+  array.insert-many(2, (3, 4))
+  test(array, (1, 2, 3, 4, 5))
+  array.insert-many(0, (-1, 0))
+  test(array, (-1, 0, 1, 2, 3, 4, 5))
+  array.insert-many(-1, ())
+  test(array, range(-1, 6))
+  array.insert-many(7, (6,))
+  test(array, range(-1, 7))
+}
+
+---
+#{
+  let array = (1, 2, 3)
+  // Error: 3-29 array index out of bounds (index: 4, len: 3)
+  array.insert-many(4, (5,))
+}
+
+---
+// Test the `swap` method.
+#{
+  let array = (1, 2, 3, 4)
+  array.swap(0, 3)
+  test(array, (4, 2, 3, 1))
+  array.swap(1, -2)
+  test(array, (4, 3, 2, 1))
+  array.swap(2, 2)
+  test(array, (4, 3, 2, 1))
+}
+
+---
+#{
+  let array = (1, 2, 3)
+  // Error: 3-19 array index out of bounds (index: 5, len: 3)
+  array.swap(0, 5)
+}
+
+---
+// Test the `splice` method.
+#{
+  let array = (1, 2, 3, 4, 5)
+  test(array.splice(1, 3, ("a", "b", "c")), (2, 3))
+  test(array, (1, "a", "b", "c", 4, 5))
+  test(array.splice(-2, none, ()), (4, 5))
+  test(array, (1, "a", "b", "c"))
+  test(array.splice(0, 0, (0,)), ())
+  test(array, (0, 1, "a", "b", "c"))
+  test(array.splice(5, none, (6, 7)), ())
+  test(array, (0, 1, "a", "b", "c", 6, 7))
+  test(array.splice(2), ("a", "b", "c", 6, 7))
+  test(array, (0, 1))
+  test(array.splice(0, 1), (0,))
+  test(array, (1,))
+}
+
+---
+#{
+  let array = (1, 2, 3)
+  // Error: 3-28 array index out of bounds (index: 4, len: 3)
+  array.splice(4, none, ())
 }
 
 ---
@@ -150,6 +253,21 @@
 #test((1, 2, 3).slice(-3, 2), (1, 2))
 #test("ABCD".split("").slice(1, -1).join("-"), "A-B-C-D")
 
+---
+// Test the `slice` method with a step.
+#test(range(10).slice(2, 8, step: 2), (2, 4, 6))
+#test(range(10).slice(1, step: 3), (1, 4, 7))
+#test(range(10).slice(8, 2, step: -2), (8, 6, 4))
+#test(range(5).slice(-1, step: -1), (4, 3, 2, 1, 0))
+#test(range(5).slice(3, 0, step: -1), (3, 2, 1))
+#test(range(5).slice(1, 3, step: -1), ())
+#test((1, 2, 3).slice(0, step: -1), (1,))
+#test(().slice(0, step: -1), ())
+
+---
+// Error: 26-27 number must not be zero
+#range(5).slice(1, step: 0)
+
 ---
 // Error: 2-30 array index out of bounds (index: 12, len: 10)
 #range(10).slice(9, count: 3)
@@ -158,23 +276,226 @@
 // Error: 2-24 array index out of bounds (index: -4, len: 3)
 #(1, 2, 3).slice(0, -4)
 
+---
+// Test the `index-of` and `last-index-of` methods.
+#test(().index-of(1), none)
+#test((1, 2, 3, 2).index-of(2), 1)
+#test((1, 2, 3, 2).last-index-of(2), 3)
+#test((1, 2, 3).index-of(4), none)
+#test((1, 2, 3).last-index-of(4), none)
+#test(("a", (1, 2), "b").index-of((1, 2)), 1)
+
+---
+// Test the `binary-search` method.
+#test(().binary-search(1), (found: false, index: 0))
+#test((1, 3, 5, 7).binary-search(5), (found: true, index: 2))
+#test((1, 3, 5, 7).binary-search(4), (found: false, index: 2))
+#test((1, 3, 5, 7).binary-search(0), (found: false, index: 0))
+#test((1, 3, 5, 7).binary-search(8), (found: false, index: 4))
+#test((1, 2.5, 3).binary-search(2.5), (found: true, index: 1))
+#test(("a", "c", "e").binary-search("d"), (found: false, index: 2))
+#test((1, 2, calc.nan).binary-search(3), (found: false, index: 2))
+
+---
+// Error: 2-30 cannot compare integer and string
+#(1, 2, 3).binary-search("a")
+
 ---
 // Test the `position` method.
 #test(("Hi", "❤️", "Love").position(s => s == "❤️"), 1)
 #test(("Bye", "💘", "Apart").position(s => s == "❤️"), none)
 #test(("A", "B", "CDEF", "G").position(v => v.len() > 2), 2)
 
+---
+// Test searching with regular expressions.
+#test(("a", "b1", "c2").find(regex("\d")), "b1")
+#test((1, "x", "y").find(regex("y")), "y")
+#test((1, 2).find(regex(".")), none)
+#test(("a", "b1", "c2").position(regex("\d")), 1)
+#test(("a1", "b", "c2").find-all(regex("\d$")), (0, 2))
+
+---
+// Error: 14-15 expected function or regex, found integer
+#(1, 2).find(1)
+
+---
+// Test the `fill` function.
+#test(array.fill(1, 0), ())
+#test(array.fill(none, 3), (none, none, none))
+#test(array.fill((1, 2), 2), ((1, 2), (1, 2)))
+
+---
+// Error: 18-20 number must be at least zero
+#array.fill("a", -1)
+
+---
+// Error: 2-26 resulting array too large
+#array.fill(0, 100000000)
+
+---
+// Test the `concat` function.
+#test(array.concat(), ())
+#test(array.concat((), ()), ())
+#test(array.concat((1, 2)), (1, 2))
+#test(array.concat((1, 2), (3,), (), (4, 5)), (1, 2, 3, 4, 5))
+#test((1,).concat((2,), (3,)), (1, 2, 3))
+
+---
+// Error: 22-23 expected array, found integer
+#array.concat((1,), 2)
+
+---
+// Test the `find-all` method.
+#test(().find-all(x => true), ())
+#test((1, 4, 3, 8, 6).find-all(calc.even), (1, 3, 4))
+#test(("a", "b", "a").find-all(s => s == "a"), (0, 2))
+#test((1, 2, 3).find-all(x => x > 5), ())
+
+---
+// Error: 18-19 expected boolean, found integer
+#(1, 2).find-all(x => x)
+
+---
+// Test the `find-windows` method.
+#test(().find-windows(2, w => true), ())
+#test((1,).find-windows(2, w => true), ())
+#test((1, 2, 1, 2, 1).find-windows(2, w => w == (1, 2)), (0, 2))
+#test((1, 2, 3, 5, 6, 7).find-windows(3, w => w.at(0) + 2 == w.at(2)), (0, 3))
+#test(("a", "b", "c").find-windows(1, w => w.len() == 1), (0, 1, 2))
+
+---
+// Error: 22-23 number must be positive
+#(1, 2).find-windows(0, w => true)
+
+---
+// Error: 25-26 expected boolean, found array
+#(1, 2).find-windows(1, w => w)
+
 ---
 // Test the `filter` method.
 #test(().filter(calc.even), ())
 #test((1, 2, 3, 4).filter(calc.even), (2, 4))
 #test((7, 3, 2, 5, 1).filter(x => x < 5), (3, 2, 1))
 
+---
+// Test the `retain` method.
+#{
+  let array = (1, 2, 3, 4, 5)
+  test(array.retain(calc.odd), 2)
+  test(array, (1, 3, 5))
+  test(array.retain(x => true), 0)
+  test(array, (1, 3, 5))
+  test(array.retain(x => x > 1 and array.len() == 3), 1)
+  test(array, (3, 5))
+  let nested = ((1, 2), (3, 4))
+  test(nested.at(1).retain(x => x > 3), 1)
+  test(nested, ((1, 2), (4,)))
+}
+
+---
+#{
+  let array = (1, 2, 3)
+  // Error: 16-17 expected boolean, found integer
+  array.retain(x => x)
+}
+
+---
+#{
+  let dict = (a: 1)
+  // Error: 3-25 type dictionary has no method `retain`
+  dict.retain(x => true)
+}
+
+---
+// Test the `extract` method.
+#{
+  let queue = (1, 2, 3, 4, 5)
+  test(queue.extract(calc.even), (2, 4))
+  test(queue, (1, 3, 5))
+  test(queue.extract(x => false), ())
+  test(queue, (1, 3, 5))
+  let copy = queue
+  test(queue.extract(x => true), (1, 3, 5))
+  test(queue, ())
+  test(copy, (1, 3, 5))
+  let nested = ((1, 2), (3, 4))
+  test(nested.at(0).extract(x => x == 1), (1,))
+  test(nested, ((2,), (3, 4)))
+}
+
+---
+#{
+  let array = (1, 2, 3)
+  // Error: 17-18 expected boolean, found integer
+  array.extract(x => x)
+}
+
+---
+#{
+  let dict = (a: 1)
+  // Error: 3-26 type dictionary has no method `extract`
+  dict.extract(x => true)
+}
+
+---
+// Test the `apply` method.
+#test((2, 10).apply(calc.pow), 1024)
+#test(().apply(() => 1), 1)
+#test((1, 2, 3).apply((..args) => args.pos().len()), 3)
+#test(((1, 2), (3, 4)).map(pair => pair.apply((a, b) => a * b)), (2, 12))
+
+---
+// Error: 15-16 unexpected argument
+#(1, 2).apply(x => x)
+
 ---
 // Test the `map` method.
 #test(().map(x => x * 2), ())
 #test((2, 3).map(x => x * 2), (4, 6))
 
+---
+// Test the `map-in-place` method.
+#{
+  let array = (1, 2, 3)
+  test(array.map-in-place(x => x * 2), none)
+  test(array, (2, 4, 6))
+  let copy = array
+  array.map-in-place(str)
+  test(array, ("2", "4", "6"))
+  test(copy, (2, 4, 6))
+  let nested = ((1, 2), (3, 4))
+  nested.at(1).map-in-place(x => -x)
+  test(nested, ((1, 2), (-3, -4)))
+}
+
+---
+// Test that the mapper of `map-in-place` sees the whole array.
+#{
+  let array = (1, 2, 3)
+  array.map-in-place(x => x + array.len())
+  test(array, (4, 5, 6))
+  let data = (values: (1, 2))
+  data.values.map-in-place(x => x + data.values.sum())
+  test(data, (values: (4, 5)))
+}
+
+---
+#{
+  let array = (1, 2, 3)
+  // Error: 27-38 cannot divide by zero
+  array.map-in-place(x => 6 / (x - 2))
+}
+
+---
+// Test the `map-indexed` method.
+#test(().map-indexed((i, x) => x), ())
+#test(("a", "b", "c").map-indexed((i, x) => x * (i + 1)), ("a", "bb", "ccc"))
+#test((5, 6).map-indexed((..args) => args.pos()), ((0, 5), (1, 6)))
+
+---
+// Error: 21-22 unexpected argument
+#(1, 2).map-indexed(x => x)
+
 ---
 // Test the `fold` method.
 #test(().fold("hi", grid), "hi")
@@ -184,6 +505,71 @@
 // Error: 20-22 unexpected argument
 #(1, 2, 3).fold(0, () => none)
 
+---
+// Test the `fold-while` method.
+#test(().fold-while(0, (s, x) => panic()), 0)
+#test((1, 2, 3).fold-while(0, (s, x) => (value: s + x)), 6)
+#test((4, 3, 5, 6, 2).fold-while(0, (s, x) => (value: s + x, done: s + x > 10)), 12)
+#test((1, 2, 3).fold-while(none, (s, x) => (value: x, done: x == 2)), 2)
+#test((1, 2, 3).fold-while(0, (s, x) => (value: s + x, done: true)), 1)
+
+---
+// Error: 23-29 dictionary does not contain key "value"
+#(1, 2).fold-while(0, (s, x) => (done: true))
+
+---
+// Error: 23-29 expected dictionary, found integer
+#(1, 2).fold-while(0, (s, x) => s + x)
+
+---
+// Test the `scan` method.
+#test(().scan(0, (s, x) => s + x), ())
+#test((1, 2, 3, 4).scan(0, (s, x) => s + x), (1, 3, 6, 10))
+#test((3, 1, 4, 1, 5).scan(0, calc.max), (3, 3, 4, 4, 5))
+#test(("a", "b").scan("", (s, x) => s + x), ("a", "ab"))
+
+---
+// Test the `fold-right` and `scan-right` methods.
+#test(().fold-right(0, (x, s) => x + s), 0)
+#test(("a", "b", "c").fold-right("", (x, s) => s + x), "cba")
+#test((1, 2, 3).fold-right("end", (x, rest) => (x, rest)), (1, (2, (3, "end"))))
+#test(().scan-right(0, (x, s) => x + s), ())
+#test((1, 2, 3, 4).scan-right(0, (x, s) => x + s), (10, 9, 7, 4))
+#test(("a", "b").scan-right("", (x, s) => s + x), ("ba", "b"))
+
+---
+// Test the `reduce-right` method.
+#test(().reduce-right((x, s) => x + s), none)
+#test((1,).reduce-right((x, s) => panic()), 1)
+#test((1, 2, 3).reduce-right((x, rest) => (x, rest)), (1, (2, 3)))
+#test((2, 3, 2).reduce-right(calc.pow), 512)
+#test((10, 4, 3).reduce-right((x, s) => x - s), 9)
+
+---
+// Test the `windows-map` method.
+#test(().windows-map(2, w => w), ())
+#test((1,).windows-map(2, w => w), ())
+#test((1, 4, 2, 8).windows-map(2, w => w.at(1) - w.at(0)), (3, -2, 6))
+#test((1, 2, 3, 4).windows-map(3, w => w), ((1, 2, 3), (2, 3, 4)))
+#test((1, 2, 3).windows-map(1, w => w.sum()), (1, 2, 3))
+
+---
+// Error: 21-22 number must be positive
+#(1, 2).windows-map(0, w => w)
+
+---
+// Test the `sliding-reduce` method.
+#test(().sliding-reduce(2, calc.max), ())
+#test((1,).sliding-reduce(2, calc.max), ())
+#test((1, 4, 2, 8, 5).sliding-reduce(2, calc.max), (4, 4, 8, 8))
+#test((1, 2, 3, 4).sliding-reduce(3, (a, b) => a + b), (6, 9))
+#test((1, 2, 3).sliding-reduce(1, (a, b) => panic()), (1, 2, 3))
+#test((2, 4, 6, 8).sliding-reduce(2, (a, b) => a + b).map(s => s / 2), (3, 5, 7))
+
+---
+// Error: 24-25 number must be positive
+#(1, 2).sliding-reduce(0, calc.max)
+
 ---
 // Test the `sum` method.
 #test(().sum(default: 0), 0)
@@ -205,10 +591,196 @@
 // Error: 2-14 cannot calculate product of empty array with no default
 #().product()
 
+---
+// Test the `sum-by` and `product-by` methods.
+#let items = ((name: "a", price: 3), (name: "b", price: 5))
+#test(items.sum-by(item => item.price), 8)
+#test(items.product-by(item => item.price), 15)
+#test(items.sum-by(item => item.name), "ab")
+#test(().sum-by(x => x, default: 0), 0)
+#test(().product-by(x => x, default: 1), 1)
+#test((2, 3).sum-by(x => x * x), 13)
+
+---
+// Error: 2-19 cannot calculate sum of empty array with no default
+#().sum-by(x => x)
+
+---
+// Error: 2-25 cannot add integer and string
+#(1, "a").sum-by(x => x)
+
+---
+// Test the `flatten` method.
+#test(().flatten(), ())
+#test(((), ((),)).flatten(), ())
+#test((1, (2, 3), ((4,), 5), (((6,),),)).flatten(), range(1, 7))
+#test(("a", ("b", ("c",)), "d").flatten(), ("a", "b", "c", "d"))
+
+---
+// Test that flattening deeply nested arrays doesn't overflow the stack. At
+// 100k levels, a recursive implementation would run out of stack on the test
+// threads. Dropping the nested array recurses as well, so it is unwrapped
+// level by level at the end.
+#{
+  let nested = (1,)
+  for _ in range(100000) {
+    nested = (nested, 2)
+  }
+  let flat = nested.flatten()
+  test(flat.len(), 100001)
+  test(flat.first(), 1)
+  test(flat.last(), 2)
+  for _ in range(100000) {
+    nested = nested.first()
+  }
+  test(nested, (1,))
+}
+
+---
+// Test the `flatten-depth` method.
+#test(().flatten-depth(1), ())
+#test((1, (2, (3, (4,)))).flatten-depth(0), (1, (2, (3, (4,)))))
+#test((1, (2, (3, (4,)))).flatten-depth(1), (1, 2, (3, (4,))))
+#test((1, (2, (3, (4,)))).flatten-depth(2), (1, 2, 3, (4,)))
+#test((1, (2, (3, (4,)))).flatten-depth(10), (1, 2, 3, 4))
+#test((1, (2, (3, (4,)))).flatten-depth(-1), (1, 2, 3, 4))
+#test(((1, 2), (), ((3,),)).flatten-depth(1), (1, 2, (3,)))
+
 ---
 // Test the `rev` method.
 #test(range(3).rev(), (2, 1, 0))
 
+---
+// Test the `step-by` method.
+#test(().step-by(2), ())
+#test(range(10).step-by(1), range(10))
+#test(range(10).step-by(3), (0, 3, 6, 9))
+#test((1, 2).step-by(5), (1,))
+
+---
+// Error: 17-18 number must be positive
+#(1, 2).step-by(0)
+
+---
+// Test the `windows` method.
+#test(().windows(2), ())
+#test((1,).windows(2), ())
+#test(range(4).windows(2), ((0, 1), (1, 2), (2, 3)))
+#test(range(5).windows(3, step: 2), ((0, 1, 2), (2, 3, 4)))
+#test(range(6).windows(2, step: 2), range(6).chunks-exact(2))
+#test(range(7).windows(2, step: 3), ((0, 1), (3, 4)))
+#test((1, 2).windows(2), ((1, 2),))
+
+---
+// Error: 17-18 number must be positive
+#(1, 2).windows(0)
+
+---
+// Error: 26-27 number must be positive
+#(1, 2).windows(1, step: 0)
+
+---
+// Test the `chunks` method.
+#test(().chunks(2), ())
+#test((1,).chunks(2), ((1,),))
+#test(range(6).chunks(2), ((0, 1), (2, 3), (4, 5)))
+#test(range(7).chunks(3), ((0, 1, 2), (3, 4, 5), (6,)))
+#test(range(7).chunks(3).join(), range(7))
+
+---
+// Error: 16-17 number must be positive
+#(1, 2).chunks(0)
+
+---
+// Test the `chunks-exact` method.
+#test(().chunks-exact(2), ())
+#test((1,).chunks-exact(2), ())
+#test(range(6).chunks-exact(2), ((0, 1), (2, 3), (4, 5)))
+#test(range(7).chunks-exact(3), ((0, 1, 2), (3, 4, 5)))
+#test((1, 2).chunks-exact(1), ((1,), (2,)))
+
+---
+// Error: 22-24 number must be positive
+#(1, 2).chunks-exact(-1)
+
+---
+// Test the `rchunks` method.
+#test(().rchunks(2), ())
+#test((1,).rchunks(2), ((1,),))
+#test(range(6).rchunks(2), ((0, 1), (2, 3), (4, 5)))
+#test(range(7).rchunks(3), ((0,), (1, 2, 3), (4, 5, 6)))
+#test("1234567".clusters().rchunks(3).map(c => c.join()), ("1", "234", "567"))
+
+---
+// Error: 17-18 number must be positive
+#(1, 2).rchunks(0)
+
+---
+// Test the `chunk-evenly` method.
+#test(().chunk-evenly(2), ((), ()))
+#test(range(6).chunk-evenly(3), ((0, 1), (2, 3), (4, 5)))
+#test(range(7).chunk-evenly(3), ((0, 1, 2), (3, 4), (5, 6)))
+#test(range(8).chunk-evenly(3), ((0, 1, 2), (3, 4, 5), (6, 7)))
+#test((1, 2).chunk-evenly(3), ((1,), (2,), ()))
+#test((1, 2).chunk-evenly(1), ((1, 2),))
+
+---
+// Error: 22-23 number must be positive
+#(1, 2).chunk-evenly(0)
+
+---
+// Test the `pad-end` and `pad-start` methods.
+#test(().pad-end(2, none), (none, none))
+#test((1, 2).pad-end(4, 0), (1, 2, 0, 0))
+#test((1, 2).pad-start(4, 0), (0, 0, 1, 2))
+#test((1, 2, 3).pad-end(2, 0), (1, 2, 3))
+#test((1, 2, 3).pad-start(3, 0), (1, 2, 3))
+#test(((1,), (2, 3)).map(row => row.pad-end(2, "-")), ((1, "-"), (2, 3)))
+
+---
+// Error: 17-19 number must be at least zero
+#(1, 2).pad-end(-1, 0)
+
+---
+// Error: 2-30 resulting array too large
+#(1, 2).pad-end(100000000, 0)
+
+---
+// Error: 2-32 resulting array too large
+#(1, 2).pad-start(100000000, 0)
+
+---
+// Test the `split` method.
+#test(().split(0), ((),))
+#test((1, 2, 3).split(0), ((1, 2, 3),))
+#test((1, 0, 2, 0, 0, 3).split(0), ((1,), (2,), (), (3,)))
+#test((0, 1, 0).split(0), ((), (1,), ()))
+#test(("a", "|", "b").split("|"), (("a",), ("b",)))
+
+---
+// Test the `chunk-by` method.
+#test(().chunk-by((a, b) => true), ())
+#test((1,).chunk-by((a, b) => false), ((1,),))
+#test((1, 2, 3, 7, 8, 10).chunk-by((a, b) => b == a + 1), ((1, 2, 3), (7, 8), (10,)))
+#test(("a", "ab", "b", "bc").chunk-by((a, b) => a.at(0) == b.at(0)), (("a", "ab"), ("b", "bc")))
+
+---
+// Error: 18-24 expected boolean, found integer
+#(1, 2).chunk-by((a, b) => a + b)
+
+---
+// Test the `segment-reduce` method.
+#test(().segment-reduce(x => x, (a, b) => a + b), ())
+#test((1, 1, 2, 2, 2, 1).segment-reduce(x => x, (a, b) => a + b), (2, 6, 1))
+#test(
+  (("a", 1), ("a", 2), ("b", 5)).segment-reduce(
+    row => row.at(0),
+    (acc, row) => (acc.at(0), acc.at(1) + row.at(1)),
+  ),
+  (("a", 3), ("b", 5)),
+)
+#test((1, 3, 2, 4, 5).segment-reduce(calc.odd, calc.max), (3, 4, 5))
+
 ---
 // Test the `join` method.
 #test(().join(), none)
@@ -216,6 +788,20 @@
 #test(("a", "b", "c").join(), "abc")
 #test("(" + ("a", "b", "c").join(", ") + ")", "(a, b, c)")
 
+---
+// Test joining arrays and dictionaries.
+#test(((1,), (2, 3)).join(), (1, 2, 3))
+#test(((a: 1, b: 2), (b: 3), (c: 4)).join(), (a: 1, b: 3, c: 4))
+#test(((a: 1), none, (b: 2)).join(), (a: 1, b: 2))
+#test(((a: 1), (b: 2)).join((z: 0)), (a: 1, z: 0, b: 2))
+
+---
+// Test the `join` method with a function separator.
+#test(("a", "bb", "c").join((a, b) => str(a.len() + b.len())), "a3bb3c")
+#test(("a", "b", "c").join((a, b) => "-"), "a-b-c")
+#test(("a", "b", "c").join(", ", last: (a, b) => " " + a + b + " "), "a, b bc c")
+#test(("a",).join((a, b) => panic()), "a")
+
 ---
 // Error: 2-22 cannot join boolean with boolean
 #(true, false).join()
@@ -229,6 +815,17 @@
 // Ref: true
 #([One], [Two], [Three]).join([, ], last: [ and ]).
 
+---
+// Test the `sequence` method.
+#test(().sequence(), [])
+#test(([A],).sequence(), [A])
+#test(([A], [B] + [C]).sequence().children.len(), 2)
+#test(([A], [B] + [C]).join().children.len(), 3)
+
+---
+// Error: 2-26 expected content, found integer (at index 1)
+#([A], 1, [B]).sequence()
+
 ---
 // Test the `intersperse` method
 #test(().intersperse("a"), ())
@@ -248,6 +845,66 @@
 #test((2, 1, 3, -10, -5, 8, 6, -7, 2).sorted(key: x => x), (-10, -7, -5, 1, 2, 2, 3, 6, 8))
 #test((2, 1, 3, -10, -5, 8, 6, -7, 2).sorted(key: x => x * x), (1, 2, 2, 3, -5, 6, -7, 8, -10))
 
+---
+// Test that NaN is sorted after all other numbers.
+#let nan = calc.nan
+#test((2.0, nan, 1, nan, -1.5, 3).sorted().map(repr), ("-1.5", "1", "2.0", "3", "NaN", "NaN"))
+#test((nan, nan).sorted().map(repr), ("NaN", "NaN"))
+#test((nan, 1, 0.5).sorted(key: x => x).map(repr), ("0.5", "1", "NaN"))
+
+---
+// Error: 2-26 cannot compare float and string
+#(calc.nan, "a").sorted()
+
+---
+// Test the `max-n` and `min-n` methods.
+#test((3, 7, 1, 9, 4).max-n(3), (9, 7, 4))
+#test((3, 7, 1, 9, 4).min-n(3), (1, 3, 4))
+#test((3, 7, 1).max-n(5), (7, 3, 1))
+#test((3, 7, 1).min-n(0), ())
+#test(().max-n(2), ())
+#test((2, 1.5, 2.0, 1).max-n(2).map(repr), ("2", "2.0"))
+#test(("b", "c", "a").min-n(2), ("a", "b"))
+#test((1, calc.nan, 2).max-n(2).map(repr), ("NaN", "2"))
+
+---
+// Error: 15-17 number must be at least zero
+#(1, 2).max-n(-1)
+
+---
+// Error: 2-22 cannot compare string and integer
+#(1, "a", 2).min-n(2)
+
+---
+// Test the `is-sorted` method.
+#test(().is-sorted(), true)
+#test((1,).is-sorted(), true)
+#test((1, 2, 2, 3).is-sorted(), true)
+#test((1, 3, 2).is-sorted(), false)
+#test((3, 2, "a").is-sorted(), false)
+#test(("a", "b", "c").is-sorted(), true)
+#test((1, 2.5, calc.nan).is-sorted(), true)
+#test((5, 2, 8, -3).sorted().is-sorted(), true)
+
+---
+// Error: 2-25 cannot compare integer and string
+#(1, 2, "a").is-sorted()
+
+---
+// Test the `sorted-total` method.
+#test(().sorted-total(), ())
+#test((3, 1, 2).sorted-total(), (1, 2, 3))
+#test((2, "b", none, 1.5, "a", true, auto).sorted-total(), (none, auto, true, 1.5, 2, "a", "b"))
+#test((3pt, (1,), false, 1).sorted-total(), (false, 1, (1,), 3pt))
+#test((1.0, 1, 1.0).sorted-total().map(type), (float, int, float))
+#test((9007199254740993, 9007199254740992.0, 9007199254740992).sorted-total(), (9007199254740992.0, 9007199254740992, 9007199254740993))
+#test((9223372036854775807, 1e19, -1e19, 2.5, 2).sorted-total(), (-1e19, 2, 2.5, 9223372036854775807, 1e19))
+#test((10pt, 3pt).sorted-total(), (3pt, 10pt))
+#test((180deg, 45deg, 90deg).sorted-total(), (45deg, 90deg, 180deg))
+#test((50%, 100%, 5%).sorted-total(), (5%, 50%, 100%))
+#test((version(10), version(9)).sorted-total(), (version(9), version(10)))
+#test((1em + 2pt, 1em, 10pt, 2pt).sorted-total(), (2pt, 10pt, 1em, 1em + 2pt))
+
 ---
 // Test the `zip` method.
 #test(().zip(()), ())
@@ -260,6 +917,53 @@
 #test((1, 2, 3).zip((3, 4, 5), (6, 7, 8)), ((1, 3, 6), (2, 4, 7), (3, 5, 8)))
 #test(().zip((), ()), ())
 #test((1,).zip((2,), (3,)), ((1, 2, 3),))
+#test((1, 2, 3).zip((4, 5, 6), (7,), (8, 9)), ((1, 4, 7, 8),))
+
+---
+// Test the `zip-with` method.
+#test(().zip-with((1,), (a, b) => a + b), ())
+#test((1, 2, 3).zip-with((10, 20, 30), (a, b) => a * b), (10, 40, 90))
+#test((1, 2, 3).zip-with(("a", "b"), (n, s) => s * n), ("a", "bb"))
+
+---
+// Error: 21-22 unexpected argument
+#(1,).zip-with((2,), a => a)
+
+---
+// Test the `transpose` method.
+#test(().transpose(), ())
+#test(((),).transpose(), ())
+#test(((1,),).transpose(), ((1,),))
+#test(((1, 2, 3), (4, 5, 6)).transpose(), ((1, 4), (2, 5), (3, 6)))
+#test(((1, 2), (3, 4)).transpose().transpose(), ((1, 2), (3, 4)))
+
+---
+// Error: 2-28 rows must have equal length
+#((1, 2), (3,)).transpose()
+
+---
+// Error: 2-25 expected array, found integer
+#((1, 2), 3).transpose()
+
+---
+// Test the `interleave` method.
+#test(().interleave(()), ())
+#test((1, 2).interleave(()), (1, 2))
+#test(().interleave((1, 2)), (1, 2))
+#test((1, 3, 5).interleave((2, 4, 6)), range(1, 7))
+#test((1, 3).interleave((2, 4, 5, 6)), range(1, 7))
+#test((1, 3, 5, 6).interleave((2, 4)), range(1, 7))
+
+---
+// Test the `cartesian-product` method.
+#test(().cartesian-product((1, 2)), ())
+#test((1, 2).cartesian-product(()), ())
+#test((1,).cartesian-product((2,)), ((1, 2),))
+#test((1, 2).cartesian-product(("a", "b")), ((1, "a"), (1, "b"), (2, "a"), (2, "b")))
+
+---
+// Error: 2-44 resulting array too large
+#range(5000).cartesian-product(range(5000))
 
 ---
 // Test the `enumerate` method.
@@ -279,6 +983,13 @@
 #test(("Jane", "John", "Eric").dedup(), ("Jane", "John", "Eric"))
 #test(("Jane", "John", "Eric", "John").dedup(), ("Jane", "John", "Eric"))
 
+---
+// Test the `count-distinct` method.
+#test(().count-distinct(), 0)
+#test((1, 1, 2, 3, 1).count-distinct(), 3)
+#test((1, 1.0, "1").count-distinct(), 2)
+#test(((1, 2), (1, 2), (2, 1)).count-distinct(), 2)
+
 ---
 // Test the `dedup` with the `key` argument.
 #test((1, 2, 3, 4, 5, 6).dedup(key: x => calc.rem(x, 2)), (1, 2))
@@ -286,6 +997,39 @@
 #test(("Hello", "World", "Hi", "There").dedup(key: x => x.len()), ("Hello", "Hi"))
 #test(("Hello", "World", "Hi", "There").dedup(key: x => x.at(0)), ("Hello", "World", "There"))
 
+---
+// Test the `tally` method.
+#test(().tally(), (:))
+#test(("a", "b", "a", "c", "a").tally(), (a: 3, b: 1, c: 1))
+#test(("b", "a", "b").tally().keys(), ("b", "a"))
+#test("hello".clusters().tally().l, 2)
+
+---
+// Error: 2-18 expected string, found integer
+#("a", 1).tally()
+
+---
+// Test the `run-length` method.
+#test(().run-length(), ())
+#test((1,).run-length(), ((1, 1),))
+#test(("a", "a", "b", "a", "a", "a").run-length(), (("a", 2), ("b", 1), ("a", 3)))
+#test((1, 1.0, 2).run-length(), ((1, 2), (2, 1)))
+#test(((1, 2), (1, 2)).run-length(), (((1, 2), 2),))
+
+---
+// Test the `dedup-by-key` method.
+#test(().dedup-by-key(x => x), ())
+#test((1, 1, 2, 1).dedup-by-key(x => x), (1, 2, 1))
+#test((1, -1, 2, 3, -3, 1).dedup-by-key(calc.abs), (1, 2, 3, 1))
+#test(
+  ((k: "a", v: 1), (k: "a", v: 2), (k: "b", v: 3)).dedup-by-key(d => d.k),
+  ((k: "a", v: 1), (k: "b", v: 3)),
+)
+
+---
+// Error: 27-32 cannot divide by zero
+#(1, 0).dedup-by-key(x => 5 / x)
+
 ---
 // Error: 32-37 cannot divide by zero
 #(1, 2, 0, 3).sorted(key: x => 5 / x)
@@ -302,6 +1046,26 @@
 // Error: 2-18 array index out of bounds (index: -4, len: 3) and no default value was specified
 #(1, 2, 3).at(-4)
 
+---
+// Test the `sample` method.
+#test((1,).sample(0), 1)
+#test(("A", "B", "C", "D").sample(7), "D")
+#test(("A", "B", "C", "D").sample(42), "B")
+#test(range(10).sample(3), range(10).sample(3))
+
+---
+// Error: 2-14 array is empty
+#().sample(1)
+
+---
+// Test the `shuffle` method.
+#test(().shuffle(0), ())
+#test((1,).shuffle(5), (1,))
+#test(range(5).shuffle(1), (2, 1, 4, 3, 0))
+#test(range(5).shuffle(2), (1, 3, 4, 2, 0))
+#test(("a", "b", "c").shuffle(0), ("c", "a", "b"))
+#test(range(20).shuffle(9).sorted(), range(20))
+
 ---
 // Error: 3-4 unclosed delimiter
 #{(}
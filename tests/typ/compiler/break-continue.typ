@@ -89,6 +89,19 @@
 
 #test(out, "AB")
 
+---
+// Test break in a callback of an array method.
+// Error: 40-45 cannot break out of a `map` callback
+#for i in range(3) { (1, 2).map(x => { break }) }
+
+---
+// Error: 23-31 cannot continue in a `filter` callback
+#(1, 2).filter(x => { continue })
+
+---
+// Error: 27-32 cannot break out of a `replace` callback
+#"ab".replace("a", m => { break })
+
 ---
 // Test continue outside of loop.
 
@@ -160,3 +173,16 @@
   }
   [#word = #roman \ ]
 }
+
+---
+// Error: 22-27 cannot break outside of loop
+// Hint: 22-27 this function was called by Typst itself, outside of any loop in your code
+#show heading: it => break
+= Heading
+
+---
+// Test that callbacks of methods in a show rule also get the hint.
+// Error: 38-43 cannot break out of a `map` callback
+// Hint: 38-43 this function was called by Typst itself, outside of any loop in your code
+#show heading: it => (1,).map(x => { break })
+= Heading
@@ -163,6 +163,33 @@
   test(greet("Typst", whatever: 10))
 }
 
+---
+// Positional parameters can also be given by name.
+#{
+  let area(width, height) = width * height
+  test(area(2, 3), 6)
+  test(area(width: 2, height: 3), 6)
+  test(area(height: 3, 2), 6)
+
+  let f(a, ..rest) = (a, rest.pos())
+  test(f(a: 1, 2, 3), (1, (2, 3)))
+
+  // Error: 7-17 missing argument: height
+  area(width: 2)
+}
+
+---
+#{
+  let area(width, height) = width * height
+  // Error: 11-19 parameter given both positionally and by name
+  area(2, width: 3)
+}
+
+---
+#let f(a, b, ..rest) = none
+// Error: 10-14 parameter given both positionally and by name
+#f(1, 2, a: 0)
+
 ---
 // Parameter unpacking.
 #let f((a, b), ..c) = (a, b, c)
@@ -186,6 +213,25 @@
 // Error: 10-19 expected identifier, found destructuring pattern
 #let f(..(a, b: c)) = none
 
+---
+// Destructuring the items of zipped and enumerated arrays.
+#test((1, 2).zip((3, 4)).map(((a, b)) => a * b), (3, 8))
+#test(("a", "b").enumerate().map(((i, v)) => str(i) + v), ("0a", "1b"))
+#test(((x: 1, y: 2),).map(((x, y)) => x + y), (3,))
+#test(((x: 1, y: 2),).map(((y: a, ..rest)) => (a, rest)), ((2, (x: 1)),))
+
+---
+// Error: 10-16 too many elements to destructure
+#{ let f((a, b)) = a + b; f((1, 2, 3)) }
+
+---
+// Error: 17-18 not enough elements to destructure
+#{ let f((a, b, c)) = a; f((1, 2)) }
+
+---
+// Error: 10-16 cannot destructure integer
+#{ let f((a, b)) = a; f(1) }
+
 ---
 // Error: 11-12 duplicate parameter: x
 #let f(x, x) = none
@@ -215,3 +261,300 @@
 ---
 // Error: 11 expected comma
 #let foo(_: 3) = none
+
+---
+// Test introspection of parameters.
+#let f(a, (b, c), d: 2, ..rest) = none
+#test(f.params().map(p => p.name), ("a", "(b, c)", "d", "rest"))
+#test(f.params().at(0), (name: "a", positional: true, named: true, variadic: false, required: true))
+#test(f.params().at(1).named, false)
+#test(f.params().at(2).default, 2)
+#test(f.params().at(3).variadic, true)
+#test("default" in f.params().at(0), false)
+#test(((..) => none).params().at(0).name, "")
+
+---
+// Test parameters of functions with pre-applied arguments.
+#let f(a, b, c: 3) = none
+#let g = f.with(1, c: 4)
+#test(g.params().map(p => p.name), ("b", "c"))
+#test(g.params().at(1).default, 4)
+
+---
+// Test parameters of built-in functions.
+#test(calc.pow.params().map(p => p.name), ("base", "exponent"))
+#test(rect.params().find(p => p.name == "fill").required, false)
+
+---
+// Test checking for named parameters.
+#let f(body, fill: red, ..rest) = none
+#test(f.has-param("fill"), true)
+#test(f.has-param("body"), true)
+#test(f.has-param("rest"), false)
+#test(f.with(fill: blue).has-param("fill"), true)
+#test(text.has-param("size"), true)
+#test(text.has-param("nope"), false)
+
+---
+// Test retrieving default values of parameters.
+#let f(body, size: 8pt) = none
+#test(f.default-of("size"), 8pt)
+#test(f.default-of("body"), none)
+#test(f.default-of("nope"), none)
+#test(f.with(size: 12pt).default-of("size"), 12pt)
+#test(calc.round.default-of("digits"), 0)
+
+---
+// Test checking for argument sinks.
+#test(((a, ..rest) => none).is-variadic(), true)
+#test(((..) => none).is-variadic(), true)
+#test(((a, b: 1) => none).is-variadic(), false)
+#test(((..rest) => none).with(1, 2).is-variadic(), true)
+#test(calc.max.is-variadic(), true)
+#test(calc.abs.is-variadic(), false)
+
+---
+// Test counting parameters.
+#let f(a, b, c: 3, ..rest) = none
+#test(f.arity(), (required: 2, optional: 1, variadic: true))
+#test(f.with(1).arity().required, 1)
+#test(f.with(1, c: 4).arity().optional, 1)
+#test(((x) => x).arity(), (required: 1, optional: 0, variadic: false))
+#test(calc.round.arity(), (required: 1, optional: 1, variadic: false))
+
+---
+// Test function composition.
+#let double(x) = 2 * x
+#let inc(x) = x + 1
+#test(double.compose(inc)(3), 8)
+#test(inc.compose(double)(3), 7)
+#test(str.compose(calc.max)(1, 5, 3), "5")
+#test(double.compose(inc).compose(inc)(1), 6)
+#test(repr(double.compose(inc)), "(..) => ..")
+
+---
+// Error: 30-31 unexpected argument
+#(x => x).compose(x => x)(1, 2)
+
+---
+// Test that pre-applied named arguments can be overridden.
+#let f(..args) = args
+#let g = f.with(1, x: 1).with(2, x: 2, y: 3)
+#test(g().pos(), (1, 2))
+#test(g().named(), (x: 2, y: 3))
+#test(g(x: 4).named(), (x: 4, y: 3))
+
+---
+// Test removing pre-applied named arguments.
+#let f(a, b: 1, c: 2) = (a, b, c)
+#test(f.with(b: 3, c: 4).without("b")(0), (0, 1, 4))
+#test(f.with(b: 3).without("b")(0), (0, 1, 2))
+#test(f.with(0, b: 3).without("c")(), (0, 3, 2))
+#test(f.without("b")(0), (0, 1, 2))
+#test(f.with(b: 3).without("b"), f)
+#let g(a, x, b: 1) = (a, x, b)
+#test(g.with-last(5, b: 3).without("b")(0), (0, 5, 1))
+#test(g.with-last(5, b: 3).without("b").params().map(p => p.name), ("a", "b"))
+#test(g.with-last(b: 3).without("b"), g)
+
+---
+// Test renaming functions.
+#let f = x => x + 1
+#let g = f.rename("inc")
+#test(repr(f), "(..) => ..")
+#test(repr(g), "inc")
+#test(g(1), 2)
+#test(repr(f.with(1).rename("one")), "one")
+#test(repr(calc.abs.rename("abs2")), "abs")
+
+---
+// Test that renamed closures can still call themselves.
+#let f(n) = if n > 0 { f(n - 1) } else { "done" }
+#let g = f.rename("g")
+#test(repr(g), "g")
+#test(g(3), "done")
+#let fac(n) = if n <= 1 { 1 } else { n * fac(n - 1) }
+#test(fac.rename("factorial")(5), 120)
+
+---
+// Test structural equality of functions.
+#let make(n) = x => x + n
+#let f = x => x + 1
+#let g = x => x + 1
+#test(f == g, false)
+#test(f.structurally-eq(g), true)
+#test(f.structurally-eq(x => x + 2), false)
+#test(make(1).structurally-eq(make(1)), true)
+#test(make(1).structurally-eq(make(2)), false)
+#test(f.with(1).structurally-eq(g.with(1)), true)
+#test(f.with(1).structurally-eq(g.with(2)), false)
+#test(calc.abs.structurally-eq(calc.abs), true)
+
+---
+// Test default values that refer to earlier parameters.
+#let size(width, height: width, area: width * height) = (width, height, area)
+#test(size(2), (2, 2, 4))
+#test(size(2, height: 3), (2, 3, 6))
+#test(size(2, area: 1), (2, 2, 1))
+#test(size.default-of("height"), none)
+#test(size.params().at(1).required, false)
+#test("default" in size.params().at(1), false)
+#let pad(body, fill: none) = none
+#test(pad.default-of("fill"), none)
+#test("default" in pad.params().at(1), true)
+
+---
+// Test that constant defaults are still evaluated at definition time.
+#let x = 1
+#let f(y: x) = y
+#let x = 2
+#test(f(), 1)
+
+---
+// Error: 11-12 unknown variable: b
+#let f(a: b, b: 1) = a
+
+---
+// Test calling functions with errors as values.
+#let parse(s) = int(s)
+#test(parse.try-call("12"), (ok: true, value: 12))
+#test(parse.try-call("twelve"), (ok: false, error: "invalid integer: twelve"))
+#test(calc.pow.try-call(0, 0).ok, false)
+#test(((a, b: 1) => a + b).try-call(1, b: 2).value, 3)
+#test((x => x).try-call().error, "missing argument: x")
+
+---
+// Test that try-call doesn't mask a call depth overflow.
+// Error: 15-21 maximum function call depth exceeded
+#let rec(n) = rec(n) + 1
+#rec.try-call(1)
+
+---
+// Test that try-call doesn't mask stray control flow.
+// Error: 17-22 cannot break outside of loop
+#let stop() = { break }
+#stop.try-call()
+
+---
+// Test currying functions.
+#let add3(a, b, c) = a + b + c
+#let f = add3.curry()
+#test(f(1)(2)(3), 6)
+#test(f(1, 2)(3), 6)
+#test(f(1)(2, 3), 6)
+#test((1, 2, 3).map(f(10)(20)), (31, 32, 33))
+#test(calc.pow.curry()(2)(10), 1024)
+#test(add3.with(1).curry()(2)(3), 6)
+
+---
+// Test that currying keeps named arguments and unary functions.
+#let scale(x, y, factor: 1) = (x + y) * factor
+#test(scale.curry()(1)(2), 3)
+#test(scale.curry()(1, factor: 2)(2), 6)
+#let inc(x) = x + 1
+#test(inc.curry() == inc, true)
+
+---
+// Error: 2-28 cannot curry a function with a variable number of arguments
+#((..args) => none).curry()
+
+---
+// Test pre-applying trailing arguments.
+#let clamp-to(x, low, high) = calc.max(low, calc.min(x, high))
+#test((-5, 3, 12).map(clamp-to.with-last(0, 10)), (0, 3, 10))
+#test(clamp-to.with-last(10)(12, 0), 10)
+#test(clamp-to.with-last(10).with(-3)(0), 0)
+#test(clamp-to.with-last(0, 10).params().map(p => p.name), ("x",))
+#test(clamp-to.with-last(10).arity().required, 2)
+#let join-with(..parts, sep: ", ") = parts.pos().join(sep)
+#test(join-with.with-last("c", sep: "-")("a", "b"), "a-b-c")
+
+---
+// Test binding a receiver as the first argument.
+#let describe(self, unit) = str(self.count) + " " + unit
+#let obj = (count: 3)
+#let obj = obj + (describe: describe.bind-self(obj))
+#test((obj.describe)("items"), "3 items")
+#test(calc.pow.bind-self(2)(3), 8)
+#test(describe.bind-self(obj).params().map(p => p.name), ("unit",))
+
+---
+// Test chaining functions with `and-then`.
+#let parse(s) = int(s)
+#let double(x) = 2 * x
+#test(parse.and-then(double)("21"), 42)
+#test(parse.and-then(double).and-then(str)("2"), "4")
+#test(parse.and-then(double).try-call("x").ok, false)
+
+---
+// Error: 17-23 invalid integer: x
+#let parse(s) = int(s)
+#parse.and-then(x => x)("x")
+
+---
+// Test applying a function repeatedly with `times`.
+#let double(x) = 2 * x
+#test(double.times(3)(1), 8)
+#test(double.times(1)(5), 10)
+#test(double.times(0)("x"), "x")
+#test(range(4).map(n => double.times(n)(1)), (1, 2, 4, 8))
+#test(calc.max.times(2)(1, 5, 3), 5)
+#test(double.times(2).params().map(p => p.name), ("x",))
+
+---
+// Error: 17-19 number must be at least zero
+#(x => x).times(-1)
+
+---
+// Error: 23-24 unexpected argument
+#(x => x).times(0)(1, 2)
+
+---
+// Lenient calls drop the extra arguments.
+#test(call-lenient((x => x).times(0), 1, 2, key: 3), 1)
+#test(call-lenient((x => 2 * x).times(2), 1, 2), 4)
+
+---
+// Test calling a function with a dictionary of named arguments.
+#let describe(name: "x", count: 0) = name + ": " + str(count)
+#test(describe.call-with-named((count: 3)), "x: 3")
+#test(describe.call-with-named((:)), "x: 0")
+#test(describe.call-with-named((name: "y", count: 1)), "y: 1")
+#test(describe.with(count: 2).call-with-named((name: "z")), "z: 2")
+
+---
+// Error: 2-45 unexpected argument: unit
+#((x: 1) => x).call-with-named((unit: "pt"))
+
+---
+// Test building a pipeline with `compose-all`.
+#let double(x) = 2 * x
+#let inc(x) = x + 1
+#test(function.compose-all(double, inc)(3), 7)
+#test(function.compose-all(inc, double)(3), 8)
+#test(function.compose-all(calc.max, str)(1, 5, 3), "5")
+#test(function.compose-all(double)(4), 8)
+#test(function.compose-all()(4), 4)
+#test(function.compose-all(..(inc,) * 5)(0), 5)
+
+---
+// Error: 28-29 unexpected argument
+#function.compose-all()(1, 2)
+
+---
+// Lenient calls drop the extra arguments.
+#test(call-lenient(function.compose-all(), 1, 2, key: 3), 1)
+
+---
+// Test the `identity` and `constant` functions.
+#test(function.identity(1), 1)
+#test((1, "a", none).map(function.identity), (1, "a", none))
+#test(function.compose-all(function.identity, str)(2), "2")
+#test(function.constant(5)(), 5)
+#test(function.constant("x")(1, 2, key: 3), "x")
+#test(range(3).map(function.constant(none)), (none, none, none))
+#test(function.constant(1).arity().variadic, true)
+
+---
+// Error: 23-24 unexpected argument
+#function.identity(1, 2)
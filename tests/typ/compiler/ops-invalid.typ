@@ -90,6 +90,10 @@
 // Error: 3-10 number must be at least zero
 #(-1 * "")
 
+---
+// Error: 3-20 resulting array too large
+#((1,) * 1000000000)
+
 ---
 // Error: 4-5 unknown variable: x
 #((x) = "")
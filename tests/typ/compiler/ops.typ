@@ -31,6 +31,10 @@
 #test("a" + if true { "b" }, "ab")
 #test(13 * "a" + "bbbbbb", "aaaaaaaaaaaaabbbbbb")
 #test((1, 2) + (3, 4), (1, 2, 3, 4))
+#test((1, 2) * 0, ())
+#test(0 * (1, 2), ())
+#test(() * 1000000000, ())
+#test((1, 2) * 2, (1, 2, 1, 2))
 #test((a: 1) + (b: 2, c: 3), (a: 1, b: 2, c: 3))
 
 ---
@@ -181,6 +185,12 @@
 #test((a: 2 - 1.0, b: 2) == (b: 2, a: 1), true)
 #test("a" != "a", false)
 
+// Arrays that share their storage are equal even if they contain `nan`.
+#let a = (1, calc.nan)
+#let b = a
+#test(a == b, true)
+#test(a == (1, calc.nan), false)
+
 // Functions compare by identity.
 #test(test == test, true)
 #test((() => {}) == (() => {}), false)
@@ -36,7 +36,51 @@
 #let rec(n) = rec(n) + 1
 #rec(1)
 
+---
+// Test that recursion through methods is caught, too.
+// Error: 15-28 maximum function call depth exceeded
+#let rec(n) = (n,).map(rec)
+#rec(1)
+
+---
+// Test that self-recursive calls in tail position don't nest.
+#let count(n, acc: 0) = if n == 0 { acc } else { count(n - 1, acc: acc + 1) }
+#test(count(1000), 1000)
+
+#let sum(xs, acc: 0) = {
+  if xs.len() == 0 { acc } else { sum(xs.slice(1), acc: acc + xs.first()) }
+}
+#test(sum(range(500)), 124750)
+
+---
+// Test that calls in non-tail position are still limited.
+// Error: 44-56 maximum function call depth exceeded
+#let count(n) = if n == 0 { 0 } else { 1 + count(n - 1) }
+#count(1000)
+
+---
+// Test that mutual recursion is still limited. The partner is passed as an
+// argument because closures capture at definition, where `b` isn't bound yet.
+#let a(f, n) = f(a, n + 1)
+// Error: 16-27 maximum function call depth exceeded
+#let b(f, n) = f(b, n + 1)
+#a(b, 0)
+
+---
+// Test that infinite tail recursion is caught.
+// Error: 20-23 maximum function call depth exceeded
+#let spin(n) = spin(n)
+#spin(1)
+
 ---
 #let f(x) = "hello"
 #let f(x) = if x != none { f(none) } else { "world" }
 #test(f(1), "world")
+
+---
+// Test that set and show rules in tail position still work.
+#let red-text() = { set text(red) }
+#test(type(red-text()), content)
+#let maybe-replace(yes) = if yes { show "a": "b" } else { none }
+#test(type(maybe-replace(true)), content)
+#test(maybe-replace(false), none)